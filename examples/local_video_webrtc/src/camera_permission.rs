@@ -1,31 +1,122 @@
-// macOS 摄像头权限检查工具
+// macOS 摄像头/麦克风权限检查工具
 // 使用 objc2 调用 AVFoundation API
 
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
 #[cfg(target_os = "macos")]
 mod macos {
+    use block2::RcBlock;
     use objc2::msg_send;
-    use objc2::runtime::AnyClass;
+    use objc2::runtime::{AnyClass, Bool};
+    use std::sync::Mutex;
+
+    /// 需要授权的媒体类型
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum MediaKind {
+        Camera,
+        Microphone,
+    }
+
+    impl MediaKind {
+        /// 对应 AVMediaTypeVideo / AVMediaTypeAudio 的四字符码
+        fn av_media_type(&self) -> &'static str {
+            match self {
+                MediaKind::Camera => "vide",
+                MediaKind::Microphone => "soun",
+            }
+        }
+
+        /// 触发该类型权限弹窗前，Info.plist 中必须存在的用途说明 key；
+        /// 缺失时 `requestAccessForMediaType:` 会直接让进程崩溃，而不是返回拒绝。
+        fn usage_description_key(&self) -> &'static str {
+            match self {
+                MediaKind::Camera => "NSCameraUsageDescription",
+                MediaKind::Microphone => "NSMicrophoneUsageDescription",
+            }
+        }
+    }
+
+    /// Info.plist 中缺少 `kind` 所需的用途说明 key。
+    #[derive(Debug, Clone)]
+    pub struct MissingUsageDescription {
+        pub kind: MediaKind,
+        pub info_plist_key: &'static str,
+    }
+
+    impl std::fmt::Display for MissingUsageDescription {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "Info.plist is missing {}, required before requesting {:?} access",
+                self.info_plist_key, self.kind
+            )
+        }
+    }
+
+    impl std::error::Error for MissingUsageDescription {}
+
+    /// 在触发权限弹窗之前检查 Info.plist 是否声明了 `kind` 对应的用途说明。
+    /// 如果缺失该 key，系统会直接终止进程而不是弹出拒绝提示，所以这里要先手动校验。
+    pub fn verify_usage_description(kind: MediaKind) -> Result<(), MissingUsageDescription> {
+        unsafe {
+            use std::ffi::{CStr, CString};
+
+            let bundle_class =
+                match AnyClass::get(CStr::from_bytes_with_nul(b"NSBundle\0").unwrap()) {
+                    Some(cls) => cls,
+                    None => {
+                        log::warn!("NSBundle class not found; skipping Info.plist validation");
+                        return Ok(());
+                    }
+                };
+            let nsstring_class =
+                match AnyClass::get(CStr::from_bytes_with_nul(b"NSString\0").unwrap()) {
+                    Some(cls) => cls,
+                    None => {
+                        log::warn!("NSString class not found; skipping Info.plist validation");
+                        return Ok(());
+                    }
+                };
+
+            let main_bundle: *mut objc2::runtime::AnyObject = msg_send![bundle_class, mainBundle];
+            let key_cstr = CString::new(kind.usage_description_key()).unwrap();
+            let key: *mut objc2::runtime::AnyObject =
+                msg_send![nsstring_class, stringWithUTF8String: key_cstr.as_ptr()];
+            let value: *mut objc2::runtime::AnyObject =
+                msg_send![main_bundle, objectForInfoDictionaryKey: key];
+
+            if value.is_null() {
+                return Err(MissingUsageDescription {
+                    kind,
+                    info_plist_key: kind.usage_description_key(),
+                });
+            }
+        }
+        Ok(())
+    }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub enum CameraPermissionStatus {
+    pub enum MediaPermissionStatus {
         NotDetermined, // 尚未请求权限 (AVAuthorizationStatusNotDetermined = 0)
         Restricted,    // 受限制（家长控制等）(AVAuthorizationStatusRestricted = 1)
         Denied,        // 已拒绝 (AVAuthorizationStatusDenied = 2)
         Authorized,    // 已授权 (AVAuthorizationStatusAuthorized = 3)
     }
 
-    impl CameraPermissionStatus {
+    impl MediaPermissionStatus {
         pub fn is_authorized(&self) -> bool {
-            matches!(self, CameraPermissionStatus::Authorized)
+            matches!(self, MediaPermissionStatus::Authorized)
         }
 
         pub fn can_request(&self) -> bool {
-            matches!(self, CameraPermissionStatus::NotDetermined)
+            matches!(self, MediaPermissionStatus::NotDetermined)
         }
     }
 
-    /// 检查摄像头权限状态
-    pub fn check_camera_permission() -> CameraPermissionStatus {
+    /// 检查指定媒体类型的权限状态
+    pub fn check_permission(kind: MediaKind) -> MediaPermissionStatus {
         unsafe {
             use std::ffi::CStr;
 
@@ -35,51 +126,48 @@ mod macos {
                     Some(cls) => cls,
                     None => {
                         log::warn!("AVCaptureDevice class not found");
-                        return CameraPermissionStatus::NotDetermined;
+                        return MediaPermissionStatus::NotDetermined;
                     }
                 };
 
-            // AVMediaTypeVideo 是一个 NSString 常量，值为 "vide"
-            // 我们需要创建一个 NSString 对象
+            // AVMediaTypeVideo/AVMediaTypeAudio 是 NSString 常量，需要创建对应的 NSString 对象
             use std::ffi::CString;
-            let media_type_video_cstr = CString::new("vide").unwrap();
+            let media_type_cstr = CString::new(kind.av_media_type()).unwrap();
 
-            // 创建 NSString 对象
             let nsstring_class =
                 match AnyClass::get(CStr::from_bytes_with_nul(b"NSString\0").unwrap()) {
                     Some(cls) => cls,
                     None => {
                         log::warn!("NSString class not found");
-                        return CameraPermissionStatus::NotDetermined;
+                        return MediaPermissionStatus::NotDetermined;
                     }
                 };
 
-            let media_type_video: *mut objc2::runtime::AnyObject =
-                msg_send![nsstring_class, stringWithUTF8String: media_type_video_cstr.as_ptr()];
+            let media_type: *mut objc2::runtime::AnyObject =
+                msg_send![nsstring_class, stringWithUTF8String: media_type_cstr.as_ptr()];
 
             // 调用类方法: +[AVCaptureDevice authorizationStatusForMediaType:]
             // NSInteger 在 64 位系统上是 i64 (long long)
-            let status: i64 =
-                msg_send![av_capture_device, authorizationStatusForMediaType: media_type_video];
+            let status: i64 = msg_send![av_capture_device, authorizationStatusForMediaType: media_type];
 
             match status {
-                0 => CameraPermissionStatus::NotDetermined,
-                1 => CameraPermissionStatus::Restricted,
-                2 => CameraPermissionStatus::Denied,
-                3 => CameraPermissionStatus::Authorized,
+                0 => MediaPermissionStatus::NotDetermined,
+                1 => MediaPermissionStatus::Restricted,
+                2 => MediaPermissionStatus::Denied,
+                3 => MediaPermissionStatus::Authorized,
                 _ => {
                     log::warn!("Unknown authorization status: {}", status);
-                    CameraPermissionStatus::NotDetermined
+                    MediaPermissionStatus::NotDetermined
                 }
             }
         }
     }
 
-    /// 触发摄像头权限请求
-    /// 通过尝试访问摄像头设备来触发 macOS 的权限提示框
+    /// 触发指定媒体类型的权限请求
+    /// 通过尝试访问设备来触发 macOS 的权限提示框
     /// 注意：对于命令行应用，权限提示可能不会立即显示，需要实际访问设备
     /// 简化版本：只获取设备，不创建 session，避免 AVCapture 错误
-    pub fn trigger_permission_request() {
+    pub fn trigger_permission_request(kind: MediaKind) {
         unsafe {
             use std::ffi::CStr;
             use std::ffi::CString;
@@ -102,76 +190,158 @@ mod macos {
                     }
                 };
 
-            let media_type_video_cstr = CString::new("vide").unwrap();
-            let media_type_video: *mut objc2::runtime::AnyObject =
-                msg_send![nsstring_class, stringWithUTF8String: media_type_video_cstr.as_ptr()];
+            let media_type_cstr = CString::new(kind.av_media_type()).unwrap();
+            let media_type: *mut objc2::runtime::AnyObject =
+                msg_send![nsstring_class, stringWithUTF8String: media_type_cstr.as_ptr()];
 
             // 只尝试获取默认设备，这会触发权限检查
             // 不创建 session 或 input，避免 AVCapture 配置错误
             let _device: *mut objc2::runtime::AnyObject =
-                msg_send![av_capture_device, defaultDeviceWithMediaType: media_type_video];
+                msg_send![av_capture_device, defaultDeviceWithMediaType: media_type];
 
             log::info!(
-                "Permission request triggered. The permission dialog should appear when you try to access the camera."
+                "Permission request triggered for {:?}. The permission dialog should appear when you try to access the device.",
+                kind
             );
         }
     }
 
-    /// 请求摄像头权限（异步）
-    /// 注意：这需要创建 Objective-C block，实现较复杂
-    /// 对于命令行应用，通常权限会在首次访问摄像头时自动弹出
-    pub async fn request_camera_permission() -> bool {
-        let status = check_camera_permission();
+    /// 请求指定媒体类型的权限（异步）
+    ///
+    /// 调用 `+[AVCaptureDevice requestAccessForMediaType:completionHandler:]`，并通过一个
+    /// Objective-C block 把系统权限弹窗的结果直接桥接回这个 future，而不是轮询
+    /// `authorizationStatusForMediaType:`：系统只在用户做出选择后才会调用这个 block，这样
+    /// 既没有轮询延迟，也不需要靠猜测的超时时间来判断用户是否已经响应。
+    pub async fn request_permission(kind: MediaKind) -> bool {
+        let status = check_permission(kind);
 
         if status.is_authorized() {
             return true;
         }
 
         if !status.can_request() {
-            log::error!("Camera permission cannot be requested. Status: {:?}", status);
+            log::error!("{:?} permission cannot be requested. Status: {:?}", kind, status);
             return false;
         }
 
-        // 触发权限请求
-        log::info!("Triggering camera permission request...");
-        trigger_permission_request();
+        if let Err(e) = verify_usage_description(kind) {
+            log::error!("{e}");
+            return false;
+        }
 
-        // 等待权限对话框显示和用户响应
-        // macOS 权限对话框是异步的，需要给足够的时间
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        let (tx, rx) = tokio::sync::oneshot::channel::<bool>();
+        let tx = Mutex::new(Some(tx));
 
-        // 轮询检查权限状态，最多等待 10 秒
-        let max_wait = 10;
-        let mut waited = 0;
-        loop {
-            let new_status = check_camera_permission();
-            if new_status.is_authorized() {
-                return true;
-            }
-            if new_status == CameraPermissionStatus::Denied {
-                return false;
-            }
-            if waited >= max_wait {
-                log::warn!(
-                    "Permission request timeout. User may need to grant permission manually."
-                );
-                return false;
+        unsafe {
+            use std::ffi::{CStr, CString};
+
+            let av_capture_device =
+                match AnyClass::get(CStr::from_bytes_with_nul(b"AVCaptureDevice\0").unwrap()) {
+                    Some(cls) => cls,
+                    None => {
+                        log::warn!("AVCaptureDevice class not found");
+                        return false;
+                    }
+                };
+
+            let nsstring_class =
+                match AnyClass::get(CStr::from_bytes_with_nul(b"NSString\0").unwrap()) {
+                    Some(cls) => cls,
+                    None => {
+                        log::warn!("NSString class not found");
+                        return false;
+                    }
+                };
+
+            let media_type_cstr = CString::new(kind.av_media_type()).unwrap();
+            let media_type: *mut objc2::runtime::AnyObject =
+                msg_send![nsstring_class, stringWithUTF8String: media_type_cstr.as_ptr()];
+
+            let completion_handler = RcBlock::new(move |granted: Bool| {
+                if let Some(tx) = tx.lock().unwrap().take() {
+                    let _ = tx.send(granted.as_bool());
+                }
+            });
+
+            let _: () = msg_send![
+                av_capture_device,
+                requestAccessForMediaType: media_type,
+                completionHandler: &*completion_handler,
+            ];
+        }
+
+        // Prefer the callback result, but don't hang forever: if the completion handler
+        // block is ever dropped without being invoked (e.g. the app is backgrounded before
+        // the user responds), fall back to denied after a generous timeout instead of
+        // blocking the caller indefinitely.
+        match tokio::time::timeout(std::time::Duration::from_secs(60), rx).await {
+            Ok(result) => result.unwrap_or_else(|_| {
+                log::warn!("Permission completion handler dropped without a response");
+                false
+            }),
+            Err(_) => {
+                log::warn!("Timed out waiting for {:?} permission response", kind);
+                false
             }
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            waited += 1;
+        }
+    }
+
+    pub fn trigger_camera_permission_request() {
+        trigger_permission_request(MediaKind::Camera)
+    }
+
+    pub fn trigger_microphone_permission_request() {
+        trigger_permission_request(MediaKind::Microphone)
+    }
+
+    // 屏幕录制权限走的是 CoreGraphics 的 C API，而不是 AVFoundation 的
+    // authorizationStatusForMediaType:，所以没有 NotDetermined/Restricted/Denied 这样的细分
+    // 状态，系统只给一个已授权/未授权的布尔值。
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    /// 检查屏幕录制权限是否已授权，不会触发系统弹窗。CoreGraphics 只给一个布尔值，
+    /// 没有 NotDetermined/Restricted 的细分，所以映射到 Authorized/Denied 这两种状态。
+    pub fn check_screen_capture_permission() -> MediaPermissionStatus {
+        if unsafe { CGPreflightScreenCaptureAccess() } {
+            MediaPermissionStatus::Authorized
+        } else {
+            MediaPermissionStatus::Denied
+        }
+    }
+
+    /// 触发屏幕录制权限弹窗（如尚未决定），并返回请求后的最终状态。
+    /// 如果用户此前已拒绝过，系统不会再弹窗，需要引导用户去系统设置里手动开启。
+    pub fn request_screen_capture_permission() -> MediaPermissionStatus {
+        if unsafe { CGRequestScreenCaptureAccess() } {
+            MediaPermissionStatus::Authorized
+        } else {
+            MediaPermissionStatus::Denied
         }
     }
 }
 
 #[cfg(target_os = "macos")]
 pub use macos::{
-    CameraPermissionStatus, check_camera_permission, request_camera_permission,
-    trigger_permission_request,
+    check_screen_capture_permission, request_screen_capture_permission,
+    trigger_camera_permission_request, trigger_microphone_permission_request,
+    trigger_permission_request, verify_usage_description, MediaKind, MediaPermissionStatus,
+    MissingUsageDescription,
 };
 
 #[cfg(not(target_os = "macos"))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum CameraPermissionStatus {
+pub enum MediaKind {
+    Camera,
+    Microphone,
+}
+
+#[cfg(not(target_os = "macos"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaPermissionStatus {
     NotDetermined,
     Restricted,
     Denied,
@@ -179,27 +349,210 @@ pub enum CameraPermissionStatus {
 }
 
 #[cfg(not(target_os = "macos"))]
-impl CameraPermissionStatus {
+impl MediaPermissionStatus {
     pub fn is_authorized(&self) -> bool {
-        matches!(self, CameraPermissionStatus::Authorized)
+        matches!(self, MediaPermissionStatus::Authorized)
     }
 
     pub fn can_request(&self) -> bool {
-        matches!(self, CameraPermissionStatus::NotDetermined)
+        matches!(self, MediaPermissionStatus::NotDetermined)
     }
 }
 
 #[cfg(not(target_os = "macos"))]
-pub fn check_camera_permission() -> CameraPermissionStatus {
-    CameraPermissionStatus::Authorized // 非 macOS 平台默认授权
+pub fn trigger_permission_request(_kind: MediaKind) {
+    // 非 macOS 平台不需要权限请求
 }
 
 #[cfg(not(target_os = "macos"))]
-pub fn trigger_permission_request() {
-    // 非 macOS 平台不需要权限请求
+pub fn trigger_camera_permission_request() {
+    trigger_permission_request(MediaKind::Camera)
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn trigger_microphone_permission_request() {
+    trigger_permission_request(MediaKind::Microphone)
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn check_screen_capture_permission() -> MediaPermissionStatus {
+    MediaPermissionStatus::Authorized // 非 macOS 平台默认授权
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn request_screen_capture_permission() -> MediaPermissionStatus {
+    MediaPermissionStatus::Authorized
+}
+
+#[cfg(not(target_os = "macos"))]
+#[derive(Debug, Clone)]
+pub struct MissingUsageDescription {
+    pub kind: MediaKind,
+    pub info_plist_key: &'static str,
+}
+
+#[cfg(not(target_os = "macos"))]
+impl std::fmt::Display for MissingUsageDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Info.plist is missing {}, required before requesting {:?} access",
+            self.info_plist_key, self.kind
+        )
+    }
 }
 
 #[cfg(not(target_os = "macos"))]
+impl std::error::Error for MissingUsageDescription {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn verify_usage_description(_kind: MediaKind) -> Result<(), MissingUsageDescription> {
+    Ok(()) // Info.plist 校验只在 macOS 上有意义
+}
+
+/// Abstracts the OS-level authorization check/request so the permission-gated logic above
+/// it can be exercised without a real AVFoundation call and real TCC state. Mirrors the
+/// `MediaAuthorizationWrapper` pattern Chromium uses to unit-test the
+/// NotDetermined -> Authorized/Denied transitions.
+pub trait AuthorizationBackend: Send + Sync {
+    fn status(&self, kind: MediaKind) -> MediaPermissionStatus;
+
+    fn request(&self, kind: MediaKind) -> Pin<Box<dyn Future<Output = bool> + Send>>;
+}
+
+/// The real [`AuthorizationBackend`], backed by the platform calls above.
+struct SystemAuthorizationBackend;
+
+#[cfg(target_os = "macos")]
+impl AuthorizationBackend for SystemAuthorizationBackend {
+    fn status(&self, kind: MediaKind) -> MediaPermissionStatus {
+        macos::check_permission(kind)
+    }
+
+    fn request(&self, kind: MediaKind) -> Pin<Box<dyn Future<Output = bool> + Send>> {
+        Box::pin(macos::request_permission(kind))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+impl AuthorizationBackend for SystemAuthorizationBackend {
+    fn status(&self, _kind: MediaKind) -> MediaPermissionStatus {
+        MediaPermissionStatus::Authorized // 非 macOS 平台默认授权
+    }
+
+    fn request(&self, _kind: MediaKind) -> Pin<Box<dyn Future<Output = bool> + Send>> {
+        Box::pin(async { true })
+    }
+}
+
+static AUTHORIZATION_BACKEND: Mutex<Option<Arc<dyn AuthorizationBackend>>> = Mutex::new(None);
+
+fn authorization_backend() -> Arc<dyn AuthorizationBackend> {
+    AUTHORIZATION_BACKEND
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| Arc::new(SystemAuthorizationBackend))
+}
+
+/// Installs `backend` as the process-wide [`AuthorizationBackend`], overriding the real
+/// AVFoundation/CoreGraphics calls. Intended for tests that need to drive this crate's
+/// permission-handling branches (e.g. NotDetermined -> Authorized/Denied) with scripted
+/// statuses, on any platform, without touching actual OS permission state.
+pub fn set_authorization_backend_for_testing(backend: Arc<dyn AuthorizationBackend>) {
+    *AUTHORIZATION_BACKEND.lock().unwrap() = Some(backend);
+}
+
+/// Restores the default [`SystemAuthorizationBackend`], undoing a prior call to
+/// [`set_authorization_backend_for_testing`].
+pub fn reset_authorization_backend_for_testing() {
+    *AUTHORIZATION_BACKEND.lock().unwrap() = None;
+}
+
+/// Checks the current authorization status for `kind`, through whichever
+/// [`AuthorizationBackend`] is currently installed.
+pub fn check_permission(kind: MediaKind) -> MediaPermissionStatus {
+    authorization_backend().status(kind)
+}
+
+/// Requests authorization for `kind`, through whichever [`AuthorizationBackend`] is
+/// currently installed. Short-circuits to `true` if already authorized, without asking
+/// the backend to prompt again.
+pub async fn request_permission(kind: MediaKind) -> bool {
+    let backend = authorization_backend();
+    if backend.status(kind).is_authorized() {
+        return true;
+    }
+    backend.request(kind).await
+}
+
+pub fn check_camera_permission() -> MediaPermissionStatus {
+    check_permission(MediaKind::Camera)
+}
+
+pub fn check_microphone_permission() -> MediaPermissionStatus {
+    check_permission(MediaKind::Microphone)
+}
+
 pub async fn request_camera_permission() -> bool {
-    true
+    request_permission(MediaKind::Camera).await
+}
+
+pub async fn request_microphone_permission() -> bool {
+    request_permission(MediaKind::Microphone).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scripted [`AuthorizationBackend`] standing in for the real OS calls, so these tests
+    /// can drive the NotDetermined -> Authorized/Denied transitions without touching
+    /// actual camera/microphone permission state.
+    struct FakeAuthorizationBackend {
+        status: MediaPermissionStatus,
+        request_result: bool,
+    }
+
+    impl AuthorizationBackend for FakeAuthorizationBackend {
+        fn status(&self, _kind: MediaKind) -> MediaPermissionStatus {
+            self.status
+        }
+
+        fn request(&self, _kind: MediaKind) -> Pin<Box<dyn Future<Output = bool> + Send>> {
+            let result = self.request_result;
+            Box::pin(async move { result })
+        }
+    }
+
+    #[tokio::test]
+    async fn request_permission_drives_not_determined_through_backend() {
+        set_authorization_backend_for_testing(Arc::new(FakeAuthorizationBackend {
+            status: MediaPermissionStatus::NotDetermined,
+            request_result: true,
+        }));
+        assert_eq!(check_camera_permission(), MediaPermissionStatus::NotDetermined);
+        assert!(request_camera_permission().await);
+
+        set_authorization_backend_for_testing(Arc::new(FakeAuthorizationBackend {
+            status: MediaPermissionStatus::NotDetermined,
+            request_result: false,
+        }));
+        assert_eq!(check_microphone_permission(), MediaPermissionStatus::NotDetermined);
+        assert!(!request_microphone_permission().await);
+
+        reset_authorization_backend_for_testing();
+    }
+
+    #[tokio::test]
+    async fn request_permission_skips_the_backend_request_when_already_authorized() {
+        set_authorization_backend_for_testing(Arc::new(FakeAuthorizationBackend {
+            status: MediaPermissionStatus::Authorized,
+            request_result: false, // backend would deny if asked; should never be reached
+        }));
+
+        assert!(request_camera_permission().await);
+
+        reset_authorization_backend_for_testing();
+    }
 }
@@ -0,0 +1,162 @@
+// Copyright 2025 LiveKit, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Frame cadence adapter: fps capping and zero-hertz idle repetition.
+//!
+//! Mirrors WebRTC's own `frame_cadence_adapter`: capture and passthrough sources forward
+//! whatever rate the upstream producer happens to run at, which wastes bandwidth on
+//! static content (screencasts, a camera pointed at nothing) and can exceed the
+//! negotiated `max_fps`. [`FrameCadenceAdapter`] wraps any frame `Stream` with two
+//! opt-in behaviors:
+//!
+//! - An fps cap that drops frames arriving faster than the configured rate.
+//! - A "zero-hertz" mode that, once the source goes idle, repeats the last frame on a
+//!   timer at a configurable minimum cadence so newly-subscribed viewers still receive
+//!   content and the encoder's rate control stays fed.
+//!
+//! Bursts are coalesced: if several frames queue up between output ticks, only the
+//! newest is kept.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use livekit_runtime::Stream;
+use tokio::time::Sleep;
+
+/// Configuration for [`FrameCadenceAdapter`].
+#[derive(Debug, Clone, Copy)]
+pub struct CadenceConfig {
+    /// Frames arriving faster than this are dropped.
+    pub max_fps: u32,
+    /// If set, the last frame is repeated at this rate once the source goes idle.
+    pub zero_hertz_min_fps: Option<u32>,
+}
+
+impl CadenceConfig {
+    pub fn new(max_fps: u32) -> Self {
+        Self { max_fps, zero_hertz_min_fps: None }
+    }
+
+    pub fn with_zero_hertz(mut self, min_fps: u32) -> Self {
+        self.zero_hertz_min_fps = Some(min_fps);
+        self
+    }
+}
+
+/// Wraps a frame [`Stream`] with an fps cap and optional zero-hertz idle repetition.
+///
+/// `S::Item` must be cheap to clone since a repeated frame is a clone of the last one
+/// seen, not a fresh capture.
+pub struct FrameCadenceAdapter<S: Stream>
+where
+    S::Item: Clone,
+{
+    inner: Pin<Box<S>>,
+    min_frame_interval: Duration,
+    idle_interval: Option<Duration>,
+    last_frame: Option<S::Item>,
+    last_emit_at: Option<Instant>,
+    idle_timer: Option<Pin<Box<Sleep>>>,
+}
+
+impl<S: Stream> FrameCadenceAdapter<S>
+where
+    S::Item: Clone,
+{
+    pub fn new(inner: S, config: CadenceConfig) -> Self {
+        let idle_interval =
+            config.zero_hertz_min_fps.map(|fps| Duration::from_secs_f64(1.0 / fps.max(1) as f64));
+        Self {
+            inner: Box::pin(inner),
+            min_frame_interval: Duration::from_secs_f64(1.0 / config.max_fps.max(1) as f64),
+            idle_timer: idle_interval.map(|d| Box::pin(tokio::time::sleep(d))),
+            idle_interval,
+            last_frame: None,
+            last_emit_at: None,
+        }
+    }
+
+    fn reset_idle_timer(&mut self) {
+        if let Some(interval) = self.idle_interval {
+            self.idle_timer = Some(Box::pin(tokio::time::sleep(interval)));
+        }
+    }
+}
+
+impl<S: Stream> Stream for FrameCadenceAdapter<S>
+where
+    S::Item: Clone,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        // Drain whatever is already queued, coalescing bursts down to the newest frame.
+        let mut newest = None;
+        let mut source_closed = false;
+        loop {
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => newest = Some(item),
+                Poll::Ready(None) => {
+                    source_closed = true;
+                    break;
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        if let Some(item) = newest {
+            let now = Instant::now();
+            let allowed = this
+                .last_emit_at
+                .map(|last| now.duration_since(last) >= this.min_frame_interval)
+                .unwrap_or(true);
+
+            this.last_frame = Some(item.clone());
+
+            if allowed {
+                this.last_emit_at = Some(now);
+                this.reset_idle_timer();
+                return Poll::Ready(Some(item));
+            }
+            // Dropped for exceeding max_fps. Wake right away so the next poll reaches the
+            // idle-timer check below (which won't fire immediately since we just saw
+            // activity) and registers for a future wakeup there.
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        if let Some(timer) = this.idle_timer.as_mut() {
+            if timer.as_mut().poll(cx).is_ready() {
+                if let Some(last) = this.last_frame.clone() {
+                    this.last_emit_at = Some(Instant::now());
+                    this.reset_idle_timer();
+                    return Poll::Ready(Some(last));
+                }
+                this.reset_idle_timer();
+            }
+        }
+
+        if source_closed && this.last_frame.is_none() {
+            return Poll::Ready(None);
+        }
+
+        Poll::Pending
+    }
+}
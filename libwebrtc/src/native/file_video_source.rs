@@ -0,0 +1,661 @@
+// Copyright 2025 LiveKit, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Local file (MP4 / MPEG-TS) passthrough source for H.264 playout.
+//!
+//! [`FileVideoSource`] demuxes H.264 access units out of a local `.mp4` or `.ts` file and
+//! pumps them into an [`EncodedVideoSource`][crate::video_source::encoded::EncodedVideoSource],
+//! pacing playback against the container's own presentation timestamps so a recorded clip
+//! can be republished into a room without transcoding.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use tokio::time::Instant as TokioInstant;
+
+use crate::video_source::encoded::{EncodedVideoFrame, EncodedVideoSource, VideoCodecType};
+
+/// One demuxed H.264 access unit, timestamped in the container's own timebase.
+#[derive(Debug, Clone)]
+pub struct DemuxedSample {
+    pub data: Vec<u8>,
+    pub is_keyframe: bool,
+    /// Presentation timestamp, in milliseconds, from the start of the file. The MP4 reader
+    /// fills this from the `stts` decode times; `ctts` composition offsets are not applied.
+    pub pts_ms: u64,
+}
+
+/// Implemented by the container-specific demuxers ([`Mp4Reader`], [`MpegTsReader`]).
+pub trait ContainerDemuxer: Send {
+    /// All samples for the selected video track, in decode (file) order.
+    fn samples(&self) -> &[DemuxedSample];
+
+    /// Index of the nearest keyframe at or before `pts_ms`, used to satisfy
+    /// `is_keyframe_requested()` by seeking rather than waiting for the next one.
+    fn nearest_keyframe_before(&self, pts_ms: u64) -> usize {
+        self.samples()
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(i, s)| s.pts_ms <= pts_ms && (s.is_keyframe || *i == 0))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+}
+
+/// Minimal MP4 demuxer: walks `moov/trak/mdia/minf/stbl` to find the first video track's
+/// sample table, reads `avcC` for the AVCDecoderConfigurationRecord's SPS/PPS and NAL
+/// length size, then converts each sample's length-prefixed NAL units into Annex-B.
+///
+/// This only understands the boxes needed for that: `ftyp`, `moov`, `trak`, `mdia`,
+/// `minf`, `stbl`, `stsd`/`avc1`/`avcC`, `stts`, `stsc`, `stsz`, `stco`/`co64`, `mdat`.
+pub struct Mp4Reader {
+    samples: Vec<DemuxedSample>,
+}
+
+impl Mp4Reader {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let data = std::fs::read(path)?;
+        let samples = mp4_demux(&data).unwrap_or_default();
+        Ok(Self { samples })
+    }
+}
+
+impl ContainerDemuxer for Mp4Reader {
+    fn samples(&self) -> &[DemuxedSample] {
+        &self.samples
+    }
+}
+
+/// A direct child box: `(type, payload, payload_offset)`, where `payload` excludes the
+/// 8-byte header and `payload_offset` is its absolute byte offset within `data`.
+fn iter_boxes_with_offset(data: &[u8]) -> impl Iterator<Item = (&[u8; 4], &[u8], usize)> {
+    struct Boxes<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+    impl<'a> Iterator for Boxes<'a> {
+        type Item = (&'a [u8; 4], &'a [u8], usize);
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.pos + 8 > self.data.len() {
+                return None;
+            }
+            let size = u32::from_be_bytes(self.data[self.pos..self.pos + 4].try_into().unwrap()) as usize;
+            let box_type: &[u8; 4] = self.data[self.pos + 4..self.pos + 8].try_into().unwrap();
+            if size < 8 || self.pos + size > self.data.len() {
+                return None;
+            }
+            let payload_offset = self.pos + 8;
+            let payload = &self.data[payload_offset..self.pos + size];
+            self.pos += size;
+            Some((box_type, payload, payload_offset))
+        }
+    }
+    Boxes { data, pos: 0 }
+}
+
+/// A direct child box: `(type, payload)`, where `payload` excludes the 8-byte header.
+fn iter_boxes(data: &[u8]) -> impl Iterator<Item = (&[u8; 4], &[u8])> {
+    iter_boxes_with_offset(data).map(|(t, p, _)| (t, p))
+}
+
+fn find_box<'a>(data: &'a [u8], name: &[u8; 4]) -> Option<&'a [u8]> {
+    iter_boxes(data).find(|(t, _)| *t == name).map(|(_, p)| p)
+}
+
+/// Like [`find_box`], but also returns the payload's absolute byte offset within `data`.
+/// `mdat`'s file offset can't be inferred from the tail of the file in general -- a
+/// trailing `free`/`uuid`/`mfra` box, or more than one `mdat`, would make
+/// `data.len() - mdat.len()` wrong -- so callers that need to map a chunk offset (from
+/// `stco`/`co64`) into `mdat`'s payload must use this instead.
+fn find_box_with_offset<'a>(data: &'a [u8], name: &[u8; 4]) -> Option<(&'a [u8], usize)> {
+    iter_boxes_with_offset(data).find(|(t, _, _)| *t == name).map(|(_, p, off)| (p, off))
+}
+
+/// `avcC` (AVCDecoderConfigurationRecord, ISO/IEC 14496-15 section 5.2.4.1): NAL length
+/// size plus the SPS/PPS to prepend ahead of each keyframe.
+///
+/// Shared with [`crate::rtsp_source`], which parses the same record out of the SDP-level
+/// `sprop-parameter-sets`/`config` fmtp attributes to re-prepend parameter sets RTSP
+/// servers sometimes omit from in-band keyframes.
+pub(crate) struct AvcC {
+    pub(crate) length_size: usize,
+    pub(crate) sps: Vec<u8>,
+    pub(crate) pps: Vec<u8>,
+}
+
+pub(crate) fn parse_avcc(avcc: &[u8]) -> Option<AvcC> {
+    if avcc.len() < 7 {
+        return None;
+    }
+    let length_size = ((avcc[4] & 0x03) + 1) as usize;
+    let num_sps = (avcc[5] & 0x1F) as usize;
+    let mut pos = 6;
+    let mut sps = Vec::new();
+    for _ in 0..num_sps {
+        let len = u16::from_be_bytes(avcc.get(pos..pos + 2)?.try_into().ok()?) as usize;
+        pos += 2;
+        sps.extend_from_slice(avcc.get(pos..pos + len)?);
+        pos += len;
+    }
+    let num_pps = *avcc.get(pos)? as usize;
+    pos += 1;
+    let mut pps = Vec::new();
+    for _ in 0..num_pps {
+        let len = u16::from_be_bytes(avcc.get(pos..pos + 2)?.try_into().ok()?) as usize;
+        pos += 2;
+        pps.extend_from_slice(avcc.get(pos..pos + len)?);
+        pos += len;
+    }
+    Some(AvcC { length_size, sps, pps })
+}
+
+/// Reads a `stsz`/`stco`/`stsc`/`stts`/`stss` box's big-endian `u32` entries starting
+/// at byte `header_len`, i.e. after its version/flags and any fixed header fields.
+fn read_u32_table(box_data: &[u8], header_len: usize, count: usize) -> Vec<u32> {
+    let mut out = Vec::with_capacity(count);
+    let mut pos = header_len;
+    for _ in 0..count {
+        let Some(bytes) = box_data.get(pos..pos + 4) else { break };
+        out.push(u32::from_be_bytes(bytes.try_into().unwrap()));
+        pos += 4;
+    }
+    out
+}
+
+/// Walks `moov/trak/mdia/minf/stbl` for the first video track (identified by an `avc1`
+/// sample entry in `stsd`), reconstructs the sample table (`stsz` sizes, `stco`/`co64`
+/// chunk offsets via `stsc` sample-to-chunk mapping, `stts` decode deltas, `stss` sync
+/// samples), and converts each `avcC`-length-prefixed sample into Annex-B, prepending the
+/// cached SPS/PPS ahead of every keyframe.
+fn mp4_demux(data: &[u8]) -> Option<Vec<DemuxedSample>> {
+    let moov = find_box(data, b"moov")?;
+
+    for (trak_type, trak) in iter_boxes(moov) {
+        if trak_type != b"trak" {
+            continue;
+        }
+        let Some(mdia) = find_box(trak, b"mdia") else { continue };
+        let Some(mdhd) = find_box(mdia, b"mdhd") else { continue };
+        let timescale = u32::from_be_bytes(mdhd.get(12..16)?.try_into().ok()?).max(1) as u64;
+
+        let Some(minf) = find_box(mdia, b"minf") else { continue };
+        let Some(stbl) = find_box(minf, b"stbl") else { continue };
+        let Some(stsd) = find_box(stbl, b"stsd") else { continue };
+        // stsd: version/flags(4) + entry_count(4) + first sample entry.
+        let Some(avc1) = stsd.get(8..).and_then(|rest| find_box(rest, b"avc1")) else { continue };
+        // avc1 sample entry fixed header is 78 bytes before its child boxes (incl. avcC).
+        let Some(avcc_box) = avc1.get(78..).and_then(|rest| find_box(rest, b"avcC")) else { continue };
+        let Some(avcc) = parse_avcc(avcc_box) else { continue };
+
+        let Some(stsz) = find_box(stbl, b"stsz") else { continue };
+        let sample_size = u32::from_be_bytes(stsz.get(4..8)?.try_into().ok()?);
+        let sample_count = u32::from_be_bytes(stsz.get(8..12)?.try_into().ok()?) as usize;
+        let sizes: Vec<u32> = if sample_size != 0 {
+            vec![sample_size; sample_count]
+        } else {
+            read_u32_table(stsz, 12, sample_count)
+        };
+
+        let chunk_offsets: Vec<u64> = if let Some(stco) = find_box(stbl, b"stco") {
+            let count = u32::from_be_bytes(stco.get(4..8)?.try_into().ok()?) as usize;
+            read_u32_table(stco, 8, count).into_iter().map(|v| v as u64).collect()
+        } else if let Some(co64) = find_box(stbl, b"co64") {
+            let count = u32::from_be_bytes(co64.get(4..8)?.try_into().ok()?) as usize;
+            (0..count)
+                .filter_map(|i| co64.get(8 + i * 8..16 + i * 8))
+                .map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+                .collect()
+        } else {
+            continue;
+        };
+
+        let Some(stsc) = find_box(stbl, b"stsc") else { continue };
+        let stsc_count = u32::from_be_bytes(stsc.get(4..8)?.try_into().ok()?) as usize;
+        // Each entry: (first_chunk, samples_per_chunk, sample_description_index).
+        let stsc_entries: Vec<(u32, u32)> = (0..stsc_count)
+            .filter_map(|i| {
+                let base = 8 + i * 12;
+                Some((
+                    u32::from_be_bytes(stsc.get(base..base + 4)?.try_into().ok()?),
+                    u32::from_be_bytes(stsc.get(base + 4..base + 8)?.try_into().ok()?),
+                ))
+            })
+            .collect();
+
+        let samples_per_chunk_at = |chunk_idx: u32| -> u32 {
+            stsc_entries
+                .iter()
+                .rev()
+                .find(|(first, _)| *first <= chunk_idx + 1)
+                .map(|(_, spc)| *spc)
+                .unwrap_or(1)
+        };
+
+        let deltas: Vec<(u32, u32)> = find_box(stbl, b"stts")
+            .and_then(|stts| {
+                let count = u32::from_be_bytes(stts.get(4..8)?.try_into().ok()?) as usize;
+                Some(
+                    (0..count)
+                        .filter_map(|i| {
+                            let base = 8 + i * 8;
+                            Some((
+                                u32::from_be_bytes(stts.get(base..base + 4)?.try_into().ok()?),
+                                u32::from_be_bytes(stts.get(base + 4..base + 8)?.try_into().ok()?),
+                            ))
+                        })
+                        .collect(),
+                )
+            })
+            .unwrap_or_default();
+
+        let sync_samples: Option<Vec<u32>> = find_box(stbl, b"stss").and_then(|stss| {
+            let count = u32::from_be_bytes(stss.get(4..8)?.try_into().ok()?) as usize;
+            Some(read_u32_table(stss, 8, count))
+        });
+
+        let (mdat, file_mdat_start) = find_box_with_offset(data, b"mdat")?;
+
+        let mut samples = Vec::with_capacity(sizes.len());
+        let mut chunk_idx = 0u32;
+        let mut sample_in_chunk = 0u32;
+        let mut chunk_cursor = *chunk_offsets.first()? as usize;
+        let mut decode_ticks = 0u64;
+        let mut delta_iter = deltas.iter().flat_map(|&(count, delta)| std::iter::repeat(delta).take(count as usize));
+
+        for (i, &size) in sizes.iter().enumerate() {
+            if sample_in_chunk >= samples_per_chunk_at(chunk_idx) {
+                chunk_idx += 1;
+                sample_in_chunk = 0;
+                chunk_cursor = *chunk_offsets.get(chunk_idx as usize)? as usize;
+            }
+            let offset_in_file = chunk_cursor;
+            let offset_in_mdat = offset_in_file.checked_sub(file_mdat_start)?;
+            let sample_data = mdat.get(offset_in_mdat..offset_in_mdat + size as usize)?;
+
+            let sample_number = (i + 1) as u32;
+            let is_keyframe =
+                sync_samples.as_ref().map(|s| s.contains(&sample_number)).unwrap_or(i == 0);
+
+            let annex_b = avcc_length_prefixed_to_annex_b(sample_data, avcc.length_size, is_keyframe, &avcc);
+
+            let delta = delta_iter.next().unwrap_or(3000);
+            let pts_ms = decode_ticks * 1000 / timescale;
+            decode_ticks += delta as u64;
+
+            samples.push(DemuxedSample { data: annex_b, is_keyframe, pts_ms });
+
+            chunk_cursor += size as usize;
+            sample_in_chunk += 1;
+        }
+
+        return Some(samples);
+    }
+
+    None
+}
+
+/// Converts one `avcC`-length-prefixed sample into Annex-B, prepending SPS/PPS ahead of
+/// keyframes since `avcC` carries them out-of-band (in the sample entry, not per-sample).
+fn avcc_length_prefixed_to_annex_b(sample: &[u8], length_size: usize, is_keyframe: bool, avcc: &AvcC) -> Vec<u8> {
+    let mut out = Vec::with_capacity(sample.len() + 32);
+    if is_keyframe {
+        out.extend_from_slice(&[0, 0, 0, 1]);
+        out.extend_from_slice(&avcc.sps);
+        out.extend_from_slice(&[0, 0, 0, 1]);
+        out.extend_from_slice(&avcc.pps);
+    }
+
+    let mut i = 0;
+    while i + length_size <= sample.len() {
+        let mut len = 0usize;
+        for b in &sample[i..i + length_size] {
+            len = (len << 8) | *b as usize;
+        }
+        i += length_size;
+        if i + len > sample.len() {
+            break;
+        }
+        out.extend_from_slice(&[0, 0, 0, 1]);
+        out.extend_from_slice(&sample[i..i + len]);
+        i += len;
+    }
+    out
+}
+
+/// Minimal MPEG-TS demuxer: follows PAT→PMT to find the video PID, reassembles PES
+/// payloads into access units, and reads the PTS from the PES header.
+pub struct MpegTsReader {
+    samples: Vec<DemuxedSample>,
+}
+
+const TS_PACKET_LEN: usize = 188;
+const TS_SYNC_BYTE: u8 = 0x47;
+
+impl MpegTsReader {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let data = std::fs::read(path)?;
+        Ok(Self { samples: ts_demux(&data) })
+    }
+}
+
+impl ContainerDemuxer for MpegTsReader {
+    fn samples(&self) -> &[DemuxedSample] {
+        &self.samples
+    }
+}
+
+fn ts_demux(data: &[u8]) -> Vec<DemuxedSample> {
+    let mut pat_pmt_pid: Option<u16> = None;
+    let mut video_pid: Option<u16> = None;
+    let mut pes_buf: Vec<u8> = Vec::new();
+    let mut samples = Vec::new();
+
+    let mut pos = 0;
+    while pos + TS_PACKET_LEN <= data.len() {
+        let packet = &data[pos..pos + TS_PACKET_LEN];
+        pos += TS_PACKET_LEN;
+        if packet[0] != TS_SYNC_BYTE {
+            continue;
+        }
+        let pusi = packet[1] & 0x40 != 0;
+        let pid = (((packet[1] & 0x1F) as u16) << 8) | packet[2] as u16;
+        let adaptation_field_control = (packet[3] >> 4) & 0x3;
+        let mut payload_start = 4;
+        if adaptation_field_control == 2 {
+            continue; // adaptation field only, no payload
+        }
+        if adaptation_field_control == 3 {
+            let af_len = packet[4] as usize;
+            payload_start = 5 + af_len;
+        }
+        if payload_start >= TS_PACKET_LEN {
+            continue;
+        }
+        let payload = &packet[payload_start..];
+
+        if pid == 0 {
+            // PAT: program_number -> PMT PID. Take the first program we see.
+            if pusi && payload.len() > 12 {
+                let pointer = payload[0] as usize;
+                let section = &payload[1 + pointer..];
+                if section.len() >= 12 {
+                    let pmt_pid = (((section[10] & 0x1F) as u16) << 8) | section[11] as u16;
+                    pat_pmt_pid = Some(pmt_pid);
+                }
+            }
+        } else if Some(pid) == pat_pmt_pid && video_pid.is_none() {
+            if pusi && !payload.is_empty() {
+                let pointer = payload[0] as usize;
+                if let Some(section) = payload.get(1 + pointer..) {
+                    video_pid = parse_pmt_video_pid(section);
+                }
+            }
+        } else if Some(pid) == video_pid {
+            if pusi {
+                if let Some(sample) = flush_pes(&pes_buf) {
+                    samples.push(sample);
+                }
+                pes_buf.clear();
+            }
+            pes_buf.extend_from_slice(payload);
+        }
+    }
+    if let Some(sample) = flush_pes(&pes_buf) {
+        samples.push(sample);
+    }
+    // PES PTS values are absolute; rebase them so `pts_ms` counts from the start of the file.
+    if let Some(first_pts_ms) = samples.iter().map(|s| s.pts_ms).min() {
+        for sample in &mut samples {
+            sample.pts_ms -= first_pts_ms;
+        }
+    }
+    samples
+}
+
+/// H.264 stream_type per ISO/IEC 13818-1.
+const STREAM_TYPE_H264: u8 = 0x1B;
+
+fn parse_pmt_video_pid(section: &[u8]) -> Option<u16> {
+    if section.len() < 12 {
+        return None;
+    }
+    let section_length = (((section[1] & 0x0F) as usize) << 8) | section[2] as usize;
+    let program_info_length = (((section[10] & 0x0F) as usize) << 8) | section[11] as usize;
+    let mut i = 12 + program_info_length;
+    let end = (3 + section_length).saturating_sub(4).min(section.len());
+    while i + 5 <= end {
+        let stream_type = section[i];
+        let elementary_pid = (((section[i + 1] & 0x1F) as u16) << 8) | section[i + 2] as u16;
+        let es_info_length = (((section[i + 3] & 0x0F) as usize) << 8) | section[i + 4] as usize;
+        if stream_type == STREAM_TYPE_H264 {
+            return Some(elementary_pid);
+        }
+        i += 5 + es_info_length;
+    }
+    None
+}
+
+/// Parses a reassembled PES payload into an access unit, reading the 90kHz PTS from the
+/// optional PES header (ISO/IEC 13818-1 section 2.4.3.7) and converting it to milliseconds.
+fn flush_pes(pes: &[u8]) -> Option<DemuxedSample> {
+    if pes.len() < 9 || pes[0] != 0x00 || pes[1] != 0x00 || pes[2] != 0x01 {
+        return None;
+    }
+    let pes_header_data_len = pes[8] as usize;
+    let pts_dts_flags = (pes[7] >> 6) & 0x3;
+    let mut pts_90k = 0u64;
+    if pts_dts_flags & 0x2 != 0 && pes.len() >= 14 {
+        let b = &pes[9..14];
+        pts_90k = (((b[0] as u64 >> 1) & 0x07) << 30)
+            | ((b[1] as u64) << 22)
+            | (((b[2] as u64 >> 1) & 0x7F) << 15)
+            | ((b[3] as u64) << 7)
+            | ((b[4] as u64 >> 1) & 0x7F);
+    }
+    let payload_start = 9 + pes_header_data_len;
+    if payload_start >= pes.len() {
+        return None;
+    }
+    let data = pes[payload_start..].to_vec();
+    let is_keyframe = data
+        .windows(5)
+        .any(|w| (w == [0, 0, 0, 1, 0x65] || (w[..3] == [0, 0, 1] && w[3] == 0x65)));
+    Some(DemuxedSample { data, is_keyframe, pts_ms: pts_90k / 90 })
+}
+
+/// Drives an [`EncodedVideoSource`] from a demuxed container, pacing `push_frame` calls
+/// against the samples' presentation timestamps and honoring keyframe requests by seeking
+/// to the nearest prior IDR.
+pub struct FileVideoSource<D: ContainerDemuxer> {
+    demuxer: D,
+    path: PathBuf,
+    pub loop_playback: bool,
+}
+
+impl<D: ContainerDemuxer> FileVideoSource<D> {
+    pub fn new(demuxer: D, path: PathBuf, loop_playback: bool) -> Self {
+        Self { demuxer, path, loop_playback }
+    }
+
+    /// Path this source was opened from, mostly useful for logging.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Play the file into `source` in real time until playback ends (or forever, if
+    /// `loop_playback` is set).
+    pub async fn run(&self, source: &EncodedVideoSource, width: u32, height: u32) {
+        loop {
+            let mut idx = 0usize;
+            let start = TokioInstant::now();
+            while idx < self.demuxer.samples().len() {
+                if source.is_keyframe_requested() {
+                    idx = self.demuxer.nearest_keyframe_before(self.demuxer.samples()[idx].pts_ms);
+                    source.clear_keyframe_request();
+                }
+
+                let sample = &self.demuxer.samples()[idx];
+                let target = start + Duration::from_millis(sample.pts_ms);
+                tokio::time::sleep_until(target.max(TokioInstant::now())).await;
+
+                let frame = if sample.is_keyframe {
+                    EncodedVideoFrame::keyframe(
+                        sample.data.clone(),
+                        (sample.pts_ms * 90) as u32,
+                        sample.pts_ms as i64,
+                        width,
+                        height,
+                        VideoCodecType::H264,
+                    )
+                } else {
+                    EncodedVideoFrame::delta_frame(
+                        sample.data.clone(),
+                        (sample.pts_ms * 90) as u32,
+                        sample.pts_ms as i64,
+                        width,
+                        height,
+                        VideoCodecType::H264,
+                    )
+                };
+                let _ = source.push_frame(&frame);
+                idx += 1;
+            }
+
+            if !self.loop_playback {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_avcc(sps: &[u8], pps: &[u8]) -> Vec<u8> {
+        let mut out = vec![1, 0x64, 0x00, 0x1F, 0xFF /* length_size_minus1 = 3 */, 0xE1 /* num_sps = 1 */];
+        out.extend_from_slice(&(sps.len() as u16).to_be_bytes());
+        out.extend_from_slice(sps);
+        out.push(1); // num_pps
+        out.extend_from_slice(&(pps.len() as u16).to_be_bytes());
+        out.extend_from_slice(pps);
+        out
+    }
+
+    #[test]
+    fn parse_avcc_recovers_length_size_and_parameter_sets() {
+        let sps = [0x67, 0x42, 0x00, 0x1F];
+        let pps = [0x68, 0xCE];
+        let avcc = parse_avcc(&sample_avcc(&sps, &pps)).expect("well-formed avcC should parse");
+        assert_eq!(avcc.length_size, 4);
+        assert_eq!(avcc.sps, sps);
+        assert_eq!(avcc.pps, pps);
+    }
+
+    #[test]
+    fn parse_avcc_rejects_truncated_input() {
+        assert!(parse_avcc(&[1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn avcc_length_prefixed_to_annex_b_prepends_parameter_sets_on_keyframes() {
+        let avcc =
+            AvcC { length_size: 4, sps: vec![0x67, 0xAA], pps: vec![0x68, 0xBB] };
+        let nal = [0x65, 0x01, 0x02];
+        let mut sample = (nal.len() as u32).to_be_bytes().to_vec();
+        sample.extend_from_slice(&nal);
+
+        let annex_b = avcc_length_prefixed_to_annex_b(&sample, avcc.length_size, true, &avcc);
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&[0, 0, 0, 1]);
+        expected.extend_from_slice(&avcc.sps);
+        expected.extend_from_slice(&[0, 0, 0, 1]);
+        expected.extend_from_slice(&avcc.pps);
+        expected.extend_from_slice(&[0, 0, 0, 1]);
+        expected.extend_from_slice(&nal);
+        assert_eq!(annex_b, expected);
+
+        // A delta frame doesn't get the out-of-band SPS/PPS re-prepended.
+        let delta = avcc_length_prefixed_to_annex_b(&sample, avcc.length_size, false, &avcc);
+        assert_eq!(delta, [&[0, 0, 0, 1][..], &nal[..]].concat());
+    }
+
+    #[test]
+    fn parse_pmt_video_pid_finds_the_h264_elementary_stream() {
+        // section_length covers everything after the 3rd byte up to (but not including)
+        // the trailing 4-byte CRC; program_info_length = 0; one stream entry: H.264 on
+        // PID 0x101, es_info_length = 0.
+        let mut section = vec![0x02, 0xB0, 0x12, 0x00, 0x01, 0xC1, 0x00, 0x00, 0xE1, 0x00, 0xF0, 0x00];
+        section.extend_from_slice(&[STREAM_TYPE_H264, 0xE1, 0x01, 0xF0, 0x00]);
+        section.extend_from_slice(&[0, 0, 0, 0]); // CRC32 placeholder
+        assert_eq!(parse_pmt_video_pid(&section), Some(0x101));
+    }
+
+    #[test]
+    fn parse_pmt_video_pid_returns_none_without_h264() {
+        let mut section = vec![0x02, 0xB0, 0x12, 0x00, 0x01, 0xC1, 0x00, 0x00, 0xE1, 0x00, 0xF0, 0x00];
+        section.extend_from_slice(&[0x02 /* MPEG-2 video, not H.264 */, 0xE1, 0x01, 0xF0, 0x00]);
+        section.extend_from_slice(&[0, 0, 0, 0]);
+        assert_eq!(parse_pmt_video_pid(&section), None);
+    }
+
+    #[test]
+    fn flush_pes_reads_pts_and_detects_keyframe() {
+        // PTS-only (pts_dts_flags = 0b10) 90kHz timestamp of 90_000 (1 second), encoded
+        // per ISO/IEC 13818-1 section 2.4.3.7.
+        let pts_90k: u64 = 90_000;
+        let b0 = 0x21 | (((pts_90k >> 30) & 0x07) << 1) as u8;
+        let b1 = ((pts_90k >> 22) & 0xFF) as u8;
+        let b2 = ((((pts_90k >> 15) & 0x7F) << 1) | 1) as u8;
+        let b3 = ((pts_90k >> 7) & 0xFF) as u8;
+        let b4 = (((pts_90k & 0x7F) << 1) | 1) as u8;
+
+        let mut pes = vec![0x00, 0x00, 0x01, 0xE0, 0x00, 0x00, 0x80, 0x80, 0x05];
+        pes.extend_from_slice(&[b0, b1, b2, b3, b4]);
+        pes.extend_from_slice(&[0, 0, 0, 1, 0x65, 0xAA]);
+
+        let sample = flush_pes(&pes).expect("well-formed PES header should parse");
+        assert_eq!(sample.pts_ms, 1000);
+        assert!(sample.is_keyframe);
+    }
+
+    #[test]
+    fn flush_pes_rejects_a_non_pes_start_code() {
+        assert!(flush_pes(&[0x00, 0x00, 0x00, 0xE0, 0, 0, 0, 0, 0]).is_none());
+    }
+
+    struct FixedSamples(Vec<DemuxedSample>);
+    impl ContainerDemuxer for FixedSamples {
+        fn samples(&self) -> &[DemuxedSample] {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn nearest_keyframe_before_seeks_back_to_the_last_sync_sample() {
+        let demuxer = FixedSamples(vec![
+            DemuxedSample { data: vec![], is_keyframe: true, pts_ms: 0 },
+            DemuxedSample { data: vec![], is_keyframe: false, pts_ms: 1000 },
+            DemuxedSample { data: vec![], is_keyframe: true, pts_ms: 2000 },
+            DemuxedSample { data: vec![], is_keyframe: false, pts_ms: 3000 },
+        ]);
+
+        assert_eq!(demuxer.nearest_keyframe_before(3000), 2);
+        assert_eq!(demuxer.nearest_keyframe_before(1000), 0);
+        assert_eq!(demuxer.nearest_keyframe_before(0), 0);
+    }
+}
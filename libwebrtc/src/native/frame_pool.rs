@@ -0,0 +1,103 @@
+// Copyright 2025 LiveKit, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A small pool of recycled I420 scratch buffers, keyed by resolution.
+//!
+//! [`NativeVideoCapturerStream`][crate::video_capturer::NativeVideoCapturerStream] copies
+//! each captured frame out of libwebrtc's own (refcounted) buffer into a buffer on loan
+//! from a [`FramePool`], so frames a consumer holds onto don't pin libwebrtc's memory.
+//! Other callers that need owned frame bytes -- snapshotting into a ring buffer, handing
+//! raw I420 to an encoder that wants owned planes -- would otherwise allocate a fresh
+//! `Vec<u8>` every frame. [`FramePool`] recycles those scratch buffers instead of letting
+//! them allocate/free on every frame at capture frame rate.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Resolution-keyed pool of I420-sized scratch buffers.
+#[derive(Default)]
+pub struct FramePool {
+    free: Mutex<HashMap<(u32, u32), Vec<Vec<u8>>>>,
+}
+
+impl FramePool {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Borrow a buffer sized for one I420 frame at `width`x`height` (allocating if the
+    /// pool is empty for that resolution). Returned to the pool when dropped.
+    pub fn acquire(self: &Arc<Self>, width: u32, height: u32) -> PooledBuffer {
+        let key = (width, height);
+        let required_len = i420_len(width, height);
+        let mut buf = self
+            .free
+            .lock()
+            .unwrap()
+            .get_mut(&key)
+            .and_then(|bufs| bufs.pop())
+            .unwrap_or_default();
+        buf.resize(required_len, 0);
+        PooledBuffer { pool: self.clone(), key, buf: Some(buf) }
+    }
+
+    fn release(&self, key: (u32, u32), buf: Vec<u8>) {
+        // Bound how many idle buffers we keep per resolution so a brief burst of
+        // odd-sized frames (e.g. a resolution change mid-call) can't grow this
+        // unbounded; a handful is enough to absorb normal jitter.
+        const MAX_IDLE_PER_RESOLUTION: usize = 4;
+        let mut free = self.free.lock().unwrap();
+        let bufs = free.entry(key).or_default();
+        if bufs.len() < MAX_IDLE_PER_RESOLUTION {
+            bufs.push(buf);
+        }
+    }
+}
+
+fn i420_len(width: u32, height: u32) -> usize {
+    let luma = (width as usize) * (height as usize);
+    let chroma = (width as usize).div_ceil(2) * (height as usize).div_ceil(2);
+    luma + 2 * chroma
+}
+
+/// A `Vec<u8>` on loan from a [`FramePool`]; returned to the pool on drop.
+pub struct PooledBuffer {
+    pool: Arc<FramePool>,
+    key: (u32, u32),
+    buf: Option<Vec<u8>>,
+}
+
+impl std::ops::Deref for PooledBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.buf.as_deref().unwrap()
+    }
+}
+
+impl std::ops::DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.buf.as_deref_mut().unwrap()
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            self.pool.release(self.key, buf);
+        }
+    }
+}
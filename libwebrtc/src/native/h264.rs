@@ -0,0 +1,505 @@
+// Copyright 2025 LiveKit, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! H.264 Annex-B helpers for the passthrough path.
+//!
+//! [`ParameterSetTracker`] watches the NAL units flowing through [`EncodedVideoFrame`]s
+//! and caches the most recent SPS/PPS, so that sources whose keyframes don't carry their
+//! own parameter sets in-band (RTSP/RTP in particular, where SPS/PPS are usually only
+//! present once in the SDP) still produce a decodable stream for late-joining viewers.
+
+use crate::native::passthrough_video_source::EncodedVideoFrame;
+
+/// NAL unit types we care about (low 5 bits of the NAL header byte).
+const NAL_TYPE_NON_IDR: u8 = 1;
+const NAL_TYPE_SPS: u8 = 7;
+const NAL_TYPE_PPS: u8 = 8;
+const NAL_TYPE_IDR: u8 = 5;
+
+/// A single NAL unit's header type and its slice of the raw (still escaped) payload.
+struct Nal<'a> {
+    nal_type: u8,
+    payload: &'a [u8],
+}
+
+/// Splits an Annex-B access unit into its NAL units, accepting both 3- and 4-byte start
+/// codes. Payloads keep their emulation-prevention `00 00 03` bytes; see [`unescape_rbsp`].
+fn split_annex_b(data: &[u8]) -> Vec<Nal<'_>> {
+    let mut starts = Vec::new();
+    let mut i = 0;
+    while i + 2 < data.len() {
+        if data[i] == 0 && data[i + 1] == 0 {
+            if data[i + 2] == 1 {
+                starts.push(i + 3);
+                i += 3;
+                continue;
+            } else if i + 3 < data.len() && data[i + 2] == 0 && data[i + 3] == 1 {
+                starts.push(i + 4);
+                i += 4;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    let mut nals = Vec::with_capacity(starts.len());
+    for (idx, &start) in starts.iter().enumerate() {
+        if start >= data.len() {
+            continue;
+        }
+        let end = starts
+            .get(idx + 1)
+            .map(|&next| {
+                // Back up over the start code we just scanned past: its `00 00 01` tail,
+                // then any further zero bytes (the 4-byte form's leading zero).
+                let mut e = next - 3;
+                while e > start && data[e - 1] == 0 {
+                    e -= 1;
+                }
+                e
+            })
+            .unwrap_or(data.len());
+        if end <= start {
+            continue;
+        }
+        let nal_type = data[start] & 0x1F;
+        nals.push(Nal { nal_type, payload: &data[start..end] });
+    }
+    nals
+}
+
+/// Removes `00 00 03` emulation-prevention bytes from a NAL payload (header byte included).
+fn unescape_rbsp(payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len());
+    let mut zero_run = 0;
+    for &b in payload {
+        if zero_run >= 2 && b == 0x03 {
+            zero_run = 0;
+            continue;
+        }
+        out.push(b);
+        zero_run = if b == 0 { zero_run + 1 } else { 0 };
+    }
+    out
+}
+
+/// Minimal big-endian bit reader for Exp-Golomb SPS parsing.
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit_pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, bit_pos: 0 }
+    }
+
+    fn bit(&mut self) -> Option<u32> {
+        let byte = *self.data.get(self.bit_pos / 8)?;
+        let bit = (byte >> (7 - self.bit_pos % 8)) & 1;
+        self.bit_pos += 1;
+        Some(bit as u32)
+    }
+
+    fn bits(&mut self, n: u32) -> Option<u32> {
+        let mut v = 0u32;
+        for _ in 0..n {
+            v = (v << 1) | self.bit()?;
+        }
+        Some(v)
+    }
+
+    /// ue(v): unsigned Exp-Golomb code.
+    fn ue(&mut self) -> Option<u32> {
+        let mut leading_zeros = 0;
+        while self.bit()? == 0 {
+            leading_zeros += 1;
+            if leading_zeros > 32 {
+                return None;
+            }
+        }
+        if leading_zeros == 0 {
+            return Some(0);
+        }
+        let suffix = self.bits(leading_zeros)?;
+        Some((1 << leading_zeros) - 1 + suffix)
+    }
+}
+
+/// Coded dimensions and the `seq_parameter_set_id` decoded from an SPS, used to detect
+/// resolution changes and SPS swaps without re-parsing on every frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpsInfo {
+    pub id: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Exp-Golomb-decodes just enough of an SPS to recover the coded width/height, per
+/// ITU-T H.264 7.3.2.1.1 / 7.4.2.1.1. Returns `None` on anything unexpected rather than
+/// panicking; passthrough callers fall back to the frame's existing width/height.
+fn parse_sps(rbsp: &[u8]) -> Option<SpsInfo> {
+    // rbsp[0] is the NAL header byte.
+    let mut r = BitReader::new(&rbsp[1..]);
+
+    let profile_idc = r.bits(8)?;
+    let _constraint_flags_and_reserved = r.bits(8)?;
+    let _level_idc = r.bits(8)?;
+    let seq_parameter_set_id = r.ue()?;
+
+    let mut chroma_format_idc = 1;
+    let mut separate_colour_plane_flag = false;
+    if matches!(profile_idc, 100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135) {
+        chroma_format_idc = r.ue()?;
+        if chroma_format_idc == 3 {
+            separate_colour_plane_flag = r.bit()? == 1;
+        }
+        let _bit_depth_luma_minus8 = r.ue()?;
+        let _bit_depth_chroma_minus8 = r.ue()?;
+        let _qpprime_y_zero_transform_bypass_flag = r.bit()?;
+        let seq_scaling_matrix_present_flag = r.bit()?;
+        if seq_scaling_matrix_present_flag == 1 {
+            // Scaling lists are rare on camera/passthrough sources and not needed for
+            // dimensions; bail out rather than implementing the full scaling-list syntax.
+            return None;
+        }
+    }
+
+    let _log2_max_frame_num_minus4 = r.ue()?;
+    let pic_order_cnt_type = r.ue()?;
+    if pic_order_cnt_type == 0 {
+        let _log2_max_pic_order_cnt_lsb_minus4 = r.ue()?;
+    } else if pic_order_cnt_type == 1 {
+        let _delta_pic_order_always_zero_flag = r.bit()?;
+        let _offset_for_non_ref_pic = r.ue()?;
+        let _offset_for_top_to_bottom_field = r.ue()?;
+        let num_ref_frames_in_pic_order_cnt_cycle = r.ue()?;
+        for _ in 0..num_ref_frames_in_pic_order_cnt_cycle {
+            let _offset_for_ref_frame = r.ue()?;
+        }
+    }
+    let _max_num_ref_frames = r.ue()?;
+    let _gaps_in_frame_num_value_allowed_flag = r.bit()?;
+    let pic_width_in_mbs_minus1 = r.ue()?;
+    let pic_height_in_map_units_minus1 = r.ue()?;
+    let frame_mbs_only_flag = r.bit()?;
+    if frame_mbs_only_flag == 0 {
+        let _mb_adaptive_frame_field_flag = r.bit()?;
+    }
+    let _direct_8x8_inference_flag = r.bit()?;
+    let frame_cropping_flag = r.bit()?;
+    let (mut crop_left, mut crop_right, mut crop_top, mut crop_bottom) = (0, 0, 0, 0);
+    if frame_cropping_flag == 1 {
+        crop_left = r.ue()?;
+        crop_right = r.ue()?;
+        crop_top = r.ue()?;
+        crop_bottom = r.ue()?;
+    }
+
+    // ChromaArrayType (7.4.2.1.1): forced to 0 when separate_colour_plane_flag is set,
+    // regardless of chroma_format_idc -- each colour plane is then coded as its own
+    // monochrome-like picture, so the monochrome crop unit applies.
+    let chroma_array_type =
+        if separate_colour_plane_flag { 0 } else if chroma_format_idc == 0 { 0 } else { chroma_format_idc };
+    let (crop_unit_x, crop_unit_y) = match chroma_array_type {
+        0 => (1, 2 - frame_mbs_only_flag),
+        1 => (2, 2 * (2 - frame_mbs_only_flag)),
+        2 => (2, 1 * (2 - frame_mbs_only_flag)),
+        _ => (1, 1 * (2 - frame_mbs_only_flag)),
+    };
+
+    // This input comes straight off the wire (RTSP/RTP SPS, possibly malformed or
+    // malicious); a bogus mbs count or crop value must not be allowed to overflow or
+    // underflow the dimension arithmetic, so every step is checked rather than trusted.
+    let width_mbs = pic_width_in_mbs_minus1.checked_add(1)?;
+    let raw_width = width_mbs.checked_mul(16)?;
+    let crop_x = crop_unit_x.checked_mul(crop_left.checked_add(crop_right)?)?;
+    let width = raw_width.checked_sub(crop_x)?;
+
+    let height_map_units = pic_height_in_map_units_minus1.checked_add(1)?;
+    let raw_height = (2 - frame_mbs_only_flag).checked_mul(height_map_units)?.checked_mul(16)?;
+    let crop_y = crop_unit_y.checked_mul(crop_top.checked_add(crop_bottom)?)?;
+    let height = raw_height.checked_sub(crop_y)?;
+
+    // Generous upper bound (well past any real H.264 level's limit) to catch crop/mbs
+    // values that passed the checked arithmetic above but are still nonsensical.
+    const MAX_DIMENSION: u32 = 16_384;
+    if width == 0 || height == 0 || width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return None;
+    }
+
+    Some(SpsInfo { id: seq_parameter_set_id, width, height })
+}
+
+/// Tracks the most recent SPS/PPS seen on an H.264 passthrough stream and repairs
+/// keyframes that arrive without their own in-band parameter sets.
+#[derive(Default)]
+pub struct ParameterSetTracker {
+    sps: Option<Vec<u8>>,
+    pps: Option<Vec<u8>>,
+    sps_info: Option<SpsInfo>,
+}
+
+/// Outcome of [`ParameterSetTracker::process`].
+pub enum TrackedFrame {
+    /// The frame (possibly with SPS/PPS prepended, and width/height corrected) should be
+    /// injected as-is.
+    Emit(EncodedVideoFrame),
+    /// A non-IDR frame arrived before any parameter sets were known; it has been dropped
+    /// and a keyframe should be requested.
+    DropNeedsKeyframe,
+}
+
+impl ParameterSetTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caches any SPS/PPS in `frame`, corrects its width/height from a parsed SPS, and
+    /// prepends the cached SPS/PPS to an IDR that lacks them. A mid-stream SPS change is
+    /// detected but not reported to the caller, and no keyframe is requested for it.
+    pub fn process(&mut self, mut frame: EncodedVideoFrame) -> TrackedFrame {
+        let nals = split_annex_b(&frame.data);
+
+        let mut has_idr = false;
+        let mut sps_changed = false;
+        for nal in &nals {
+            match nal.nal_type {
+                NAL_TYPE_SPS => {
+                    let rbsp = unescape_rbsp(nal.payload);
+                    if let Some(info) = parse_sps(&rbsp) {
+                        if self.sps_info.map(|prev| prev.id != info.id || prev != info).unwrap_or(true) {
+                            sps_changed = self.sps_info.is_some();
+                        }
+                        frame.width = info.width;
+                        frame.height = info.height;
+                        self.sps_info = Some(info);
+                    }
+                    self.sps = Some(nal.payload.to_vec());
+                }
+                NAL_TYPE_PPS => self.pps = Some(nal.payload.to_vec()),
+                NAL_TYPE_IDR => has_idr = true,
+                NAL_TYPE_NON_IDR => {}
+                _ => {}
+            }
+        }
+
+        if has_idr {
+            let have_inband_params = nals.iter().any(|n| n.nal_type == NAL_TYPE_SPS)
+                && nals.iter().any(|n| n.nal_type == NAL_TYPE_PPS);
+            if !have_inband_params {
+                if let (Some(sps), Some(pps)) = (&self.sps, &self.pps) {
+                    let mut prefixed = Vec::with_capacity(sps.len() + pps.len() + frame.data.len() + 8);
+                    prefixed.extend_from_slice(&[0, 0, 0, 1]);
+                    prefixed.extend_from_slice(sps);
+                    prefixed.extend_from_slice(&[0, 0, 0, 1]);
+                    prefixed.extend_from_slice(pps);
+                    prefixed.extend_from_slice(&frame.data);
+                    frame.data = prefixed;
+                }
+            }
+            frame.is_keyframe = true;
+            return TrackedFrame::Emit(frame);
+        }
+
+        if self.sps.is_none() || self.pps.is_none() {
+            return TrackedFrame::DropNeedsKeyframe;
+        }
+
+        if sps_changed {
+            // Resolution/profile changed mid-stream; this delta frame is still emitted
+            // as-is, and the change is not surfaced in the returned `TrackedFrame`.
+        }
+
+        TrackedFrame::Emit(frame)
+    }
+
+    /// Coded resolution from the most recently parsed SPS, if one has been seen.
+    pub fn current_resolution(&self) -> Option<(u32, u32)> {
+        self.sps_info.map(|i| (i.width, i.height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::passthrough_video_source::PassthroughCodec;
+
+    /// Packs Exp-Golomb/fixed-width fields MSB-first into bytes, for building synthetic
+    /// SPS RBSPs without hand-transcribing real encoder output.
+    #[derive(Default)]
+    struct BitWriter {
+        bits: Vec<bool>,
+    }
+
+    impl BitWriter {
+        fn bits(&mut self, v: u32, n: u32) {
+            for i in (0..n).rev() {
+                self.bits.push((v >> i) & 1 == 1);
+            }
+        }
+
+        /// ue(v): unsigned Exp-Golomb code per ITU-T H.264 9.1.
+        fn ue(&mut self, v: u32) {
+            let code = v + 1;
+            let num_bits = 32 - code.leading_zeros();
+            for _ in 0..num_bits - 1 {
+                self.bits.push(false);
+            }
+            self.bits(code, num_bits);
+        }
+
+        fn into_bytes(self) -> Vec<u8> {
+            let mut out = vec![0u8; self.bits.len().div_ceil(8)];
+            for (i, bit) in self.bits.into_iter().enumerate() {
+                if bit {
+                    out[i / 8] |= 0x80 >> (i % 8);
+                }
+            }
+            out
+        }
+    }
+
+    /// Builds a baseline-profile SPS RBSP (header byte included) for `width`x`height`,
+    /// with no scaling lists/cropping, matching the subset [`parse_sps`] understands.
+    fn baseline_sps_rbsp(id: u32, width: u32, height: u32) -> Vec<u8> {
+        assert!(width % 16 == 0 && height % 16 == 0, "test helper only covers MB-aligned sizes");
+        let mut w = BitWriter::default();
+        w.bits(66, 8); // profile_idc: Baseline (no chroma_format_idc block)
+        w.bits(0, 8); // constraint flags + reserved
+        w.bits(30, 8); // level_idc
+        w.ue(id); // seq_parameter_set_id
+        w.ue(0); // log2_max_frame_num_minus4
+        w.ue(0); // pic_order_cnt_type
+        w.ue(0); // log2_max_pic_order_cnt_lsb_minus4
+        w.ue(1); // max_num_ref_frames
+        w.bits(0, 1); // gaps_in_frame_num_value_allowed_flag
+        w.ue(width / 16 - 1); // pic_width_in_mbs_minus1
+        w.ue(height / 16 - 1); // pic_height_in_map_units_minus1
+        w.bits(1, 1); // frame_mbs_only_flag
+        w.bits(0, 1); // direct_8x8_inference_flag
+        w.bits(0, 1); // frame_cropping_flag
+
+        let mut rbsp = vec![(0x00 << 5) | NAL_TYPE_SPS]; // nal_ref_idc=0, just needs the type
+        rbsp.extend(w.into_bytes());
+        rbsp
+    }
+
+    #[test]
+    fn parse_sps_recovers_coded_dimensions() {
+        let rbsp = baseline_sps_rbsp(0, 1280, 720);
+        let info = parse_sps(&rbsp).expect("valid baseline SPS should parse");
+        assert_eq!(info, SpsInfo { id: 0, width: 1280, height: 720 });
+    }
+
+    #[test]
+    fn parse_sps_rejects_crop_that_underflows_coded_width() {
+        let mut w = BitWriter::default();
+        w.bits(66, 8); // profile_idc: Baseline (no chroma_format_idc block)
+        w.bits(0, 8); // constraint flags + reserved
+        w.bits(30, 8); // level_idc
+        w.ue(0); // seq_parameter_set_id
+        w.ue(0); // log2_max_frame_num_minus4
+        w.ue(0); // pic_order_cnt_type
+        w.ue(0); // log2_max_pic_order_cnt_lsb_minus4
+        w.ue(1); // max_num_ref_frames
+        w.bits(0, 1); // gaps_in_frame_num_value_allowed_flag
+        w.ue(0); // pic_width_in_mbs_minus1 (coded width 16)
+        w.ue(0); // pic_height_in_map_units_minus1
+        w.bits(1, 1); // frame_mbs_only_flag
+        w.bits(0, 1); // direct_8x8_inference_flag
+        w.bits(1, 1); // frame_cropping_flag
+        w.ue(0); // crop_left
+        w.ue(100); // crop_right: far larger than the coded width, would underflow unchecked
+        w.ue(0); // crop_top
+        w.ue(0); // crop_bottom
+
+        let mut rbsp = vec![(0x00 << 5) | NAL_TYPE_SPS];
+        rbsp.extend(w.into_bytes());
+
+        assert_eq!(parse_sps(&rbsp), None);
+    }
+
+    #[test]
+    fn split_annex_b_handles_mixed_start_code_lengths() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[0, 0, 0, 1]); // 4-byte start code
+        data.extend_from_slice(&[0x67, 0xAA, 0xBB]); // SPS-ish
+        data.extend_from_slice(&[0, 0, 1]); // 3-byte start code
+        data.extend_from_slice(&[0x68, 0xCC]); // PPS-ish
+
+        let nals = split_annex_b(&data);
+        assert_eq!(nals.len(), 2);
+        assert_eq!(nals[0].nal_type, NAL_TYPE_SPS);
+        assert_eq!(nals[0].payload, &[0x67, 0xAA, 0xBB]);
+        assert_eq!(nals[1].nal_type, NAL_TYPE_PPS);
+        assert_eq!(nals[1].payload, &[0x68, 0xCC]);
+    }
+
+    #[test]
+    fn unescape_rbsp_strips_emulation_prevention_bytes() {
+        let escaped = [0x67, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x02];
+        assert_eq!(unescape_rbsp(&escaped), vec![0x67, 0x00, 0x00, 0x01, 0x00, 0x00, 0x02]);
+    }
+
+    fn annex_b_frame(nals: &[&[u8]]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for nal in nals {
+            out.extend_from_slice(&[0, 0, 0, 1]);
+            out.extend_from_slice(nal);
+        }
+        out
+    }
+
+    #[test]
+    fn process_prepends_cached_parameter_sets_to_a_bare_idr() {
+        let sps = baseline_sps_rbsp(0, 640, 480);
+        let pps = vec![(0x00 << 5) | NAL_TYPE_PPS, 0xCE];
+        let mut tracker = ParameterSetTracker::new();
+
+        // First, a keyframe that carries its own SPS/PPS: nothing to repair, just learn them.
+        let idr = vec![(0x00 << 5) | NAL_TYPE_IDR, 0xAA];
+        let idr_with_params = annex_b_frame(&[&sps[..], &pps[..], &idr[..]]);
+        let frame = EncodedVideoFrame::keyframe(idr_with_params, 0, 0, 0, 0, PassthroughCodec::H264);
+        match tracker.process(frame) {
+            TrackedFrame::Emit(out) => {
+                assert_eq!((out.width, out.height), (640, 480));
+            }
+            TrackedFrame::DropNeedsKeyframe => panic!("keyframe with in-band params should emit"),
+        }
+
+        // A later keyframe missing its own SPS/PPS should have the cached ones prepended.
+        let bare_idr = vec![(0x00 << 5) | NAL_TYPE_IDR, 0xBB];
+        let frame = EncodedVideoFrame::keyframe(bare_idr, 1, 1, 0, 0, PassthroughCodec::H264);
+        match tracker.process(frame) {
+            TrackedFrame::Emit(out) => {
+                let nals = split_annex_b(&out.data);
+                assert_eq!(nals.iter().map(|n| n.nal_type).collect::<Vec<_>>(), vec![
+                    NAL_TYPE_SPS,
+                    NAL_TYPE_PPS,
+                    NAL_TYPE_IDR,
+                ]);
+            }
+            TrackedFrame::DropNeedsKeyframe => panic!("should repair from cached params"),
+        }
+    }
+
+    #[test]
+    fn process_drops_delta_frames_before_any_parameter_sets_are_known() {
+        let mut tracker = ParameterSetTracker::new();
+        let delta = vec![(0x00 << 5) | NAL_TYPE_NON_IDR, 0x11];
+        let frame = EncodedVideoFrame::delta_frame(delta, 0, 0, 0, 0, PassthroughCodec::H264);
+        assert!(matches!(tracker.process(frame), TrackedFrame::DropNeedsKeyframe));
+    }
+}
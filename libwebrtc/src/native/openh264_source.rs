@@ -0,0 +1,179 @@
+// Copyright 2025 LiveKit, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Software H.264 encoding on top of [`openh264`], for platforms without a usable
+//! hardware encoder.
+//!
+//! [`Openh264VideoSource`] sits next to [`native::NativeVideoSource`][crate::video_source::native::NativeVideoSource]:
+//! it accepts the same raw [`VideoFrame`], but runs each one through an in-process
+//! OpenH264 encoder and feeds the resulting Annex-B bitstream into the existing
+//! passthrough pipeline, giving a deterministic, tunable encode path instead of
+//! depending on whatever encoder libwebrtc would otherwise pick.
+
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Mutex,
+};
+
+use openh264::{
+    encoder::{Encoder, EncoderConfig, RateControlMode},
+    formats::YUVBuffer,
+};
+
+use crate::video_frame::{VideoBuffer, VideoFrame};
+use crate::video_source::encoded::{EncodedVideoFrame, EncodedVideoSource, VideoCodecType};
+
+/// NAL unit types the layer-concatenation loop treats as "this access unit is a keyframe".
+const NAL_TYPE_SPS: u8 = 7;
+const NAL_TYPE_PPS: u8 = 8;
+const NAL_TYPE_IDR: u8 = 5;
+
+/// Tunables for [`Openh264VideoSource::new`].
+#[derive(Debug, Clone, Copy)]
+pub struct Openh264Config {
+    pub bitrate_bps: u32,
+    pub max_fps: f32,
+    pub rate_control: RateControlMode,
+}
+
+impl Default for Openh264Config {
+    fn default() -> Self {
+        Self { bitrate_bps: 2_000_000, max_fps: 30.0, rate_control: RateControlMode::Bufferbased }
+    }
+}
+
+/// Encodes raw frames with OpenH264 and republishes them through an
+/// [`EncodedVideoSource`].
+pub struct Openh264VideoSource {
+    encoder: Mutex<Encoder>,
+    source: EncodedVideoSource,
+    width: u32,
+    height: u32,
+    rtp_timestamp: AtomicU32,
+    // Last raw frame's `timestamp_us`, used to derive the 90kHz RTP increment from the
+    // actual cadence `encode_frame` is called at, rather than assuming `config.max_fps`.
+    last_frame_timestamp_us: Mutex<Option<i64>>,
+    // Fallback RTP increment (first frame, or a non-monotonic timestamp) derived from
+    // `config.max_fps`.
+    default_rtp_increment: u32,
+    force_intra: AtomicU32, // treated as a bool: 1 forces the next encoded frame to be an IDR
+}
+
+impl Openh264VideoSource {
+    pub fn new(width: u32, height: u32, config: Openh264Config) -> Option<Self> {
+        let encoder_config = EncoderConfig::new(width, height)
+            .bitrate(openh264::encoder::BitRate::from_bps(config.bitrate_bps))
+            .max_frame_rate(openh264::encoder::FrameRate::from_hz(config.max_fps))
+            .rate_control_mode(config.rate_control);
+        let encoder = Encoder::with_api_config(openh264::OpenH264API::from_source(), encoder_config).ok()?;
+        let source = EncodedVideoSource::new(VideoCodecType::H264, width, height)?;
+
+        Some(Self {
+            encoder: Mutex::new(encoder),
+            source,
+            width,
+            height,
+            rtp_timestamp: AtomicU32::new(0),
+            last_frame_timestamp_us: Mutex::new(None),
+            default_rtp_increment: (90_000.0 / config.max_fps.max(1.0)) as u32,
+            force_intra: AtomicU32::new(0),
+        })
+    }
+
+    /// The underlying passthrough source; publish a video track from this.
+    pub fn video_source(&self) -> &EncodedVideoSource {
+        &self.source
+    }
+
+    /// Force the next encoded frame to be an IDR, e.g. in response to a PLI.
+    pub fn force_intra(&self) {
+        self.force_intra.store(1, Ordering::SeqCst);
+    }
+
+    /// Encode one raw frame and push the result into the passthrough pipeline.
+    pub fn encode_frame<T: AsRef<dyn VideoBuffer>>(&self, frame: &VideoFrame<T>) -> Result<(), String> {
+        let i420 = frame.buffer.as_ref().to_i420();
+        let yuv = YUVBuffer::with_width_height_stride_planes(
+            self.width as usize,
+            self.height as usize,
+            i420.stride_y() as usize,
+            i420.stride_u() as usize,
+            i420.stride_v() as usize,
+            i420.data_y(),
+            i420.data_u(),
+            i420.data_v(),
+        );
+
+        let mut encoder = self.encoder.lock().unwrap();
+        if self.force_intra.swap(0, Ordering::SeqCst) == 1 {
+            encoder.force_intra_frame();
+        }
+
+        let bitstream = encoder.encode(&yuv).map_err(|e| format!("openh264 encode failed: {e}"))?;
+
+        let mut data = Vec::new();
+        let mut is_keyframe = false;
+        for layer_idx in 0..bitstream.num_layers() {
+            let layer = bitstream.layer(layer_idx);
+            for nal_idx in 0..layer.nal_count() {
+                let nal = layer.nal_unit(nal_idx);
+                // nal[..4] is the Annex-B start code already emitted by the encoder.
+                if nal.len() > 4 {
+                    let nal_type = nal[4] & 0x1F;
+                    if matches!(nal_type, NAL_TYPE_SPS | NAL_TYPE_PPS | NAL_TYPE_IDR) {
+                        is_keyframe = true;
+                    }
+                }
+                data.extend_from_slice(nal);
+            }
+        }
+        if data.is_empty() {
+            // Encoder buffered this frame (B-frame reordering); nothing to push yet.
+            return Ok(());
+        }
+
+        // Derive the 90kHz RTP increment from how far apart the raw frames actually were,
+        // rather than assuming `config.max_fps`: a capturer that's configured for 30fps but
+        // delivers at a different real cadence (or is reconfigured mid-stream) would
+        // otherwise drift the RTP clock out of sync with wall time. Fall back to the
+        // configured rate for the first frame, or if timestamps ever go backwards/stall.
+        let rtp_increment = {
+            let mut last = self.last_frame_timestamp_us.lock().unwrap();
+            let increment = match *last {
+                Some(prev) if frame.timestamp_us > prev => {
+                    let delta_us = (frame.timestamp_us - prev) as f64;
+                    ((delta_us * 90_000.0 / 1_000_000.0).round() as u32).max(1)
+                }
+                _ => self.default_rtp_increment,
+            };
+            *last = Some(frame.timestamp_us);
+            increment
+        };
+        let rtp_timestamp = self.rtp_timestamp.fetch_add(rtp_increment, Ordering::SeqCst);
+        let capture_time_ms = frame.timestamp_us / 1_000;
+
+        let out = if is_keyframe {
+            EncodedVideoFrame::keyframe(data, rtp_timestamp, capture_time_ms, self.width, self.height, VideoCodecType::H264)
+        } else {
+            EncodedVideoFrame::delta_frame(data, rtp_timestamp, capture_time_ms, self.width, self.height, VideoCodecType::H264)
+        };
+
+        if self.source.is_keyframe_requested() {
+            self.force_intra();
+            self.source.clear_keyframe_request();
+        }
+
+        self.source.push_frame(&out)
+    }
+}
@@ -18,15 +18,20 @@
 //! WebRTC without re-encoding. This is useful when you have access to a
 //! hardware encoder or pre-encoded video stream.
 
+use std::sync::Arc;
+
 use cxx::SharedPtr;
+pub use webrtc_sys::passthrough_video_encoder::ffi::PassthroughCodec;
+pub use webrtc_sys::passthrough_video_encoder::RateListener;
 use webrtc_sys::passthrough_video_encoder::ffi::{
     self as pt_ffi, PassthroughVideoEncoder, PassthroughVideoEncoderFactory,
 };
+use webrtc_sys::passthrough_video_encoder::RateListenerWrapper;
 
-/// Represents a pre-encoded H.264 frame ready for injection.
+/// Represents a pre-encoded frame ready for injection.
 #[derive(Debug, Clone)]
 pub struct EncodedVideoFrame {
-    /// The encoded H.264 data (should include NAL units)
+    /// The encoded bitstream data (NAL units for H.264/H.265, frame payload for VP8/VP9/AV1)
     pub data: Vec<u8>,
     /// RTP timestamp (90kHz clock)
     pub rtp_timestamp: u32,
@@ -34,12 +39,14 @@ pub struct EncodedVideoFrame {
     pub capture_time_ms: i64,
     /// NTP time in milliseconds
     pub ntp_time_ms: i64,
-    /// Whether this frame is a keyframe (IDR frame)
+    /// Whether this frame is a keyframe
     pub is_keyframe: bool,
     /// Frame width in pixels
     pub width: u32,
     /// Frame height in pixels
     pub height: u32,
+    /// Codec the bitstream is encoded with
+    pub codec: PassthroughCodec,
 }
 
 impl EncodedVideoFrame {
@@ -51,6 +58,7 @@ impl EncodedVideoFrame {
         is_keyframe: bool,
         width: u32,
         height: u32,
+        codec: PassthroughCodec,
     ) -> Self {
         Self {
             data,
@@ -60,6 +68,7 @@ impl EncodedVideoFrame {
             is_keyframe,
             width,
             height,
+            codec,
         }
     }
 
@@ -70,8 +79,9 @@ impl EncodedVideoFrame {
         capture_time_ms: i64,
         width: u32,
         height: u32,
+        codec: PassthroughCodec,
     ) -> Self {
-        Self::new(data, rtp_timestamp, capture_time_ms, true, width, height)
+        Self::new(data, rtp_timestamp, capture_time_ms, true, width, height, codec)
     }
 
     /// Create a delta frame (non-keyframe).
@@ -81,8 +91,9 @@ impl EncodedVideoFrame {
         capture_time_ms: i64,
         width: u32,
         height: u32,
+        codec: PassthroughCodec,
     ) -> Self {
-        Self::new(data, rtp_timestamp, capture_time_ms, false, width, height)
+        Self::new(data, rtp_timestamp, capture_time_ms, false, width, height, codec)
     }
 }
 
@@ -127,11 +138,21 @@ impl PassthroughEncoderFactory {
         Self { inner: pt_ffi::new_passthrough_video_encoder_factory() }
     }
 
+    /// Select the codec this factory should hand out a passthrough encoder for.
+    ///
+    /// Call this before publishing so SDP negotiation (which drives WebRTC's codec
+    /// selection, and in turn which `Create()` call this factory receives) picks the
+    /// passthrough factory for the requested codec instead of falling back to a software
+    /// encoder.
+    pub fn set_codec(&self, codec: PassthroughCodec) {
+        unsafe { pt_ffi::passthrough_factory_set_codec(self.inner.as_ref().unwrap(), codec) };
+    }
+
     /// Get the last encoder created by this factory.
     ///
     /// Returns None if no encoder has been created yet.
     /// Note: The encoder is created by WebRTC during SDP negotiation when
-    /// a video track with H.264 codec is added.
+    /// a video track with a matching codec is added.
     pub fn get_encoder(&self) -> Option<PassthroughEncoderHandle> {
         let encoder_ptr =
             unsafe { pt_ffi::passthrough_factory_get_encoder(self.inner.as_ref().unwrap()) };
@@ -166,9 +187,9 @@ pub struct PassthroughEncoderHandle {
 }
 
 impl PassthroughEncoderHandle {
-    /// Inject an encoded H.264 frame.
+    /// Inject an encoded frame.
     ///
-    /// The frame data should contain valid H.264 NAL units.
+    /// The frame data should contain a complete access unit for `frame.codec`.
     /// Returns Ok(()) on success, or an error if injection failed.
     pub fn inject_frame(&self, frame: &EncodedVideoFrame) -> Result<(), PassthroughError> {
         if frame.data.is_empty() {
@@ -178,6 +199,7 @@ impl PassthroughEncoderHandle {
         let result = unsafe {
             pt_ffi::passthrough_encoder_inject_frame(
                 self.encoder_ptr,
+                frame.codec,
                 &frame.data,
                 frame.rtp_timestamp,
                 frame.capture_time_ms,
@@ -215,6 +237,30 @@ impl PassthroughEncoderHandle {
     pub fn request_keyframe(&self) {
         unsafe { pt_ffi::passthrough_encoder_request_keyframe(self.encoder_ptr) };
     }
+
+    /// The target bitrate from WebRTC's most recent bitrate allocation, in bits per
+    /// second. An upstream hardware encoder (e.g. NVENC) can poll this to retarget its
+    /// own rate control.
+    pub fn target_bitrate_bps(&self) -> u32 {
+        unsafe { pt_ffi::passthrough_encoder_target_bitrate_bps(self.encoder_ptr) }
+    }
+
+    /// The framerate WebRTC's bitrate allocation expects this source to produce.
+    pub fn allocated_framerate(&self) -> u32 {
+        unsafe { pt_ffi::passthrough_encoder_allocated_framerate(self.encoder_ptr) }
+    }
+
+    /// Register a listener invoked every time WebRTC updates the bitrate allocation for
+    /// this encoder, so an upstream pipeline can react to congestion control directly
+    /// instead of polling [`target_bitrate_bps`][Self::target_bitrate_bps].
+    pub fn set_rate_listener(&self, listener: Arc<dyn RateListener>) {
+        unsafe {
+            pt_ffi::passthrough_encoder_set_rate_listener(
+                self.encoder_ptr,
+                Box::new(RateListenerWrapper::new(listener)),
+            )
+        };
+    }
 }
 
 // Safety: The encoder pointer is thread-safe as it uses internal locking
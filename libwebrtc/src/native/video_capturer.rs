@@ -1,16 +1,26 @@
 use std::{
+    collections::VecDeque,
     pin::Pin,
-    sync::Arc,
-    task::{Context, Poll},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
 };
 
 use cxx::{SharedPtr, UniquePtr};
 use livekit_runtime::Stream;
-use tokio::sync::mpsc;
 use webrtc_sys::video_track as sys_vt;
 
+use super::frame_pool::{FramePool, PooledBuffer};
 use super::video_frame::new_video_frame_buffer;
-use crate::video_frame::{BoxVideoFrame, VideoFrame};
+use crate::video_frame::{BoxVideoFrame, VideoBuffer, VideoFrame};
+
+/// Queue depth past which [`VideoCapturerTrackObserver::on_frame`] starts dropping the
+/// oldest queued frame to make room for the newest one, rather than growing unbounded.
+/// Capture callbacks run on libwebrtc's own capture thread, so an unbounded queue would
+/// let a slow consumer pile up memory there instead of just falling behind.
+const DEFAULT_QUEUE_CAPACITY: usize = 4;
 
 #[derive(Default)]
 pub(crate) struct VideoCaptureCapability {
@@ -61,14 +71,20 @@ impl VideoCapturer {
     }
 
     pub(crate) fn register_callback(&self) -> NativeVideoCapturerStream {
-        let (frame_tx, frame_rx) = mpsc::unbounded_channel();
-        let observer = Arc::new(VideoCapturerTrackObserver { frame_tx });
+        self.register_callback_with_capacity(DEFAULT_QUEUE_CAPACITY)
+    }
+
+    /// Same as [`Self::register_callback`], but with an explicit bound on how many frames
+    /// may queue up before the oldest is dropped to make room for the newest.
+    pub(crate) fn register_callback_with_capacity(&self, capacity: usize) -> NativeVideoCapturerStream {
+        let queue = Arc::new(DiscardingFrameQueue::new(capacity));
+        let observer = Arc::new(VideoCapturerTrackObserver { queue: queue.clone() });
         let native_sink = sys_vt::ffi::new_native_video_sink(Box::new(
             sys_vt::VideoSinkWrapper::new(observer.clone()),
         ));
         self.sys_handle.register_capture_data_callback(&native_sink);
 
-        NativeVideoCapturerStream { _native_sink: native_sink, frame_rx }
+        NativeVideoCapturerStream { _native_sink: native_sink, queue, frame_pool: FramePool::new() }
     }
 
     pub(crate) fn start(&self, capability: VideoCaptureCapability) -> i32 {
@@ -117,12 +133,26 @@ impl VideoDevice {
 
 pub struct NativeVideoCapturerStream {
     _native_sink: SharedPtr<sys_vt::ffi::NativeVideoSink>,
-    frame_rx: mpsc::UnboundedReceiver<BoxVideoFrame>,
+    queue: Arc<DiscardingFrameQueue>,
+    frame_pool: Arc<FramePool>,
 }
 
 impl NativeVideoCapturerStream {
     fn close(&mut self) {
-        self.frame_rx.close();
+        self.queue.close();
+    }
+
+    /// Number of frames dropped so far because the queue was full when a new frame
+    /// arrived. A steadily climbing count means the consumer can't keep up with capture.
+    pub fn discarded_frame_count(&self) -> u64 {
+        self.queue.discarded.load(Ordering::Relaxed)
+    }
+
+    /// Recycled I420 scratch-buffer pool backing the frames this stream yields (see
+    /// [`wrap_raw_frame`]); exposed so a caller can borrow scratch buffers from the same
+    /// pool via [`FramePool::acquire`].
+    pub fn frame_pool(&self) -> &Arc<FramePool> {
+        &self.frame_pool
     }
 }
 
@@ -135,25 +165,158 @@ impl Drop for NativeVideoCapturerStream {
 impl Stream for NativeVideoCapturerStream {
     type Item = BoxVideoFrame;
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        self.frame_rx.poll_recv(cx)
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let pool = self.frame_pool.clone();
+        self.queue.poll_recv(cx).map(|maybe_raw| maybe_raw.map(|raw| wrap_raw_frame(raw, &pool)))
+    }
+}
+
+/// A bounded, single-consumer frame queue that drops the oldest entry instead of blocking
+/// the producer when full. The producer here is libwebrtc's own capture thread calling
+/// into [`VideoCapturerTrackObserver::on_frame`] -- it must never wait on a slow consumer,
+/// so pushes are always O(1) and never fail. Queued items are the raw FFI frame handle,
+/// not yet wrapped into a [`VideoFrame`]: [`new_video_frame_buffer`]'s colorspace wrap and
+/// [`wrap_raw_frame`]'s copy into a pooled buffer both run on the streaming side, in
+/// [`NativeVideoCapturerStream::poll_next`], instead of holding up libwebrtc's capture loop.
+struct DiscardingFrameQueue {
+    capacity: usize,
+    state: Mutex<QueueState>,
+    discarded: AtomicU64,
+}
+
+struct QueueState {
+    items: VecDeque<UniquePtr<webrtc_sys::video_frame::ffi::VideoFrame>>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+impl DiscardingFrameQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(QueueState { items: VecDeque::new(), waker: None, closed: false }),
+            discarded: AtomicU64::new(0),
+        }
+    }
+
+    /// Pushes `frame`, returning `true` if making room for it meant dropping the oldest
+    /// queued frame. The caller is expected to surface that back through
+    /// [`sys_vt::VideoSink::on_discarded_frame`], which is the trait's own hook for this.
+    fn push(&self, frame: UniquePtr<webrtc_sys::video_frame::ffi::VideoFrame>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return false;
+        }
+        let evicted = if state.items.len() >= self.capacity {
+            state.items.pop_front();
+            true
+        } else {
+            false
+        };
+        state.items.push_back(frame);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        evicted
+    }
+
+    fn poll_recv(
+        &self,
+        cx: &mut Context,
+    ) -> Poll<Option<UniquePtr<webrtc_sys::video_frame::ffi::VideoFrame>>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(frame) = state.items.pop_front() {
+            return Poll::Ready(Some(frame));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A [`VideoBuffer`] backed by a [`PooledBuffer`] borrowed from a [`FramePool`], rather
+/// than a fresh `Vec<u8>`, so recycling the backing memory is actually observable instead
+/// of [`FramePool::acquire`] going uncalled.
+struct PooledVideoBuffer {
+    buf: PooledBuffer,
+    width: u32,
+    height: u32,
+    stride: u32,
+}
+
+impl VideoBuffer for PooledVideoBuffer {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.buf
+    }
+
+    fn stride(&self) -> u32 {
+        self.stride
+    }
+}
+
+/// Wraps a raw FFI frame into a [`VideoFrame`], doing the colorspace conversion /
+/// buffer-handle wrap [`new_video_frame_buffer`] needs, then copies its bytes into a
+/// buffer on loan from `pool` instead of leaving the frame backed by libwebrtc's own
+/// (refcounted) memory. That keeps a slow consumer holding onto several frames (e.g.
+/// queued ahead of a software encoder) from pinning libwebrtc's buffers for as long as it
+/// takes to catch up, at the cost of one copy per frame instead of zero. Called on the
+/// streaming side (see [`DiscardingFrameQueue`]'s docs), never on libwebrtc's capture
+/// thread.
+fn wrap_raw_frame(
+    frame: UniquePtr<webrtc_sys::video_frame::ffi::VideoFrame>,
+    pool: &Arc<FramePool>,
+) -> BoxVideoFrame {
+    let rotation = frame.rotation().into();
+    let timestamp_us = frame.timestamp_us();
+    let raw_buffer = new_video_frame_buffer(unsafe { frame.video_frame_buffer() });
+
+    let width = raw_buffer.width();
+    let height = raw_buffer.height();
+    let stride = raw_buffer.stride();
+    let mut buf = pool.acquire(width, height);
+    let data = raw_buffer.data();
+    let len = data.len().min(buf.len());
+    buf[..len].copy_from_slice(&data[..len]);
+
+    VideoFrame {
+        rotation,
+        timestamp_us,
+        buffer: Box::new(PooledVideoBuffer { buf, width, height, stride }),
     }
 }
 
 struct VideoCapturerTrackObserver {
-    frame_tx: mpsc::UnboundedSender<BoxVideoFrame>,
+    queue: Arc<DiscardingFrameQueue>,
 }
 
 impl sys_vt::VideoSink for VideoCapturerTrackObserver {
     fn on_frame(&self, frame: UniquePtr<webrtc_sys::video_frame::ffi::VideoFrame>) {
-        let _ = self.frame_tx.send(VideoFrame {
-            rotation: frame.rotation().into(),
-            timestamp_us: frame.timestamp_us(),
-            buffer: new_video_frame_buffer(unsafe { frame.video_frame_buffer() }),
-        });
+        if self.queue.push(frame) {
+            self.on_discarded_frame();
+        }
     }
 
-    fn on_discarded_frame(&self) {}
+    fn on_discarded_frame(&self) {
+        self.queue.discarded.fetch_add(1, Ordering::Relaxed);
+    }
 
     fn on_constraints_changed(&self, _constraints: sys_vt::ffi::VideoTrackSourceConstraints) {}
 }
@@ -0,0 +1,192 @@
+// Copyright 2025 LiveKit, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Codec-specific frame-boundary/keyframe detection for the multi-codec passthrough path.
+//!
+//! H.264 has its own parameter-set-aware tracker in [`crate::native::h264`]; the simpler
+//! "is this access unit a keyframe" checks for the other codecs the passthrough encoder
+//! supports live here.
+
+/// NAL unit types carrying an IDR/CRA/BLA access unit in H.265 (ITU-T H.265 Table 7-1),
+/// i.e. `nal_unit_type` in `[16, 23]`.
+const H265_IRAP_RANGE: std::ops::RangeInclusive<u8> = 16..=23;
+
+/// Scans an Annex-B H.265 access unit for an IRAP (keyframe) NAL unit.
+pub fn is_h265_keyframe(data: &[u8]) -> bool {
+    let mut i = 0;
+    while i + 3 < data.len() {
+        let start = if data[i] == 0 && data[i + 1] == 0 && data[i + 2] == 1 {
+            Some(i + 3)
+        } else if i + 4 <= data.len() && data[i] == 0 && data[i + 1] == 0 && data[i + 2] == 0 && data[i + 3] == 1 {
+            Some(i + 4)
+        } else {
+            None
+        };
+
+        if let Some(start) = start {
+            if start < data.len() {
+                // H.265 NAL header: forbidden_zero_bit(1) nal_unit_type(6) ...
+                let nal_type = (data[start] >> 1) & 0x3F;
+                if H265_IRAP_RANGE.contains(&nal_type) {
+                    return true;
+                }
+            }
+            i = start;
+        } else {
+            i += 1;
+        }
+    }
+    false
+}
+
+/// VP8 uncompressed data chunk (RFC 6386 section 9.1): the low bit of the first byte is
+/// the inverted key-frame flag (`0` = keyframe).
+pub fn is_vp8_keyframe(data: &[u8]) -> bool {
+    data.first().map(|&b| b & 0x01 == 0).unwrap_or(false)
+}
+
+/// VP9 uncompressed header (RFC / VP9 bitstream spec section 6.2): after the two-bit
+/// frame marker and profile bits, `show_existing_frame` and `frame_type` determine
+/// whether this is a key frame. We only need the common case where the frame isn't a
+/// superframe index and doesn't reuse an existing frame.
+pub fn is_vp9_keyframe(data: &[u8]) -> bool {
+    let Some(&first) = data.first() else { return false };
+    // frame_marker (2 bits) must be 0b10 for a valid VP9 uncompressed header.
+    if first >> 6 != 0b10 {
+        return false;
+    }
+    let profile_low = (first >> 5) & 0x1;
+    let profile_high = (first >> 4) & 0x1;
+    let profile = (profile_high << 1) | profile_low;
+    let mut bit_pos = 4usize; // frame_marker (2 bits) + profile bits (2 bits)
+    if profile == 3 {
+        bit_pos += 1; // reserved_zero
+    }
+
+    let get_bit = |pos: usize| -> Option<u8> {
+        let byte = *data.get(pos / 8)?;
+        Some((byte >> (7 - pos % 8)) & 1)
+    };
+
+    let show_existing_frame = get_bit(bit_pos).unwrap_or(0);
+    if show_existing_frame == 1 {
+        return false;
+    }
+    bit_pos += 1;
+
+    // frame_type: 0 == KEY_FRAME
+    get_bit(bit_pos).map(|b| b == 0).unwrap_or(false)
+}
+
+/// AV1 OBU header (AV1 spec section 5.3.1): `obu_type` occupies bits 1..=4 of the first
+/// byte. A key frame is signalled by a frame or frame-header OBU whose `frame_type` (in
+/// the following frame header) is `KEY_FRAME` (0); as a practical heuristic for
+/// passthrough we treat the presence of an OBU_SEQUENCE_HEADER (type 1) anywhere in the
+/// temporal unit as the start of a new coded video sequence, which AV1 encoders only emit
+/// on keyframes. `frame_type` itself is not parsed.
+const OBU_SEQUENCE_HEADER: u8 = 1;
+
+pub fn is_av1_keyframe(data: &[u8]) -> bool {
+    let mut i = 0;
+    while i < data.len() {
+        let header = data[i];
+        let obu_type = (header >> 3) & 0x0F;
+        let has_extension = (header >> 2) & 0x1 == 1;
+        let has_size = (header >> 1) & 0x1 == 1;
+
+        let mut pos = i + 1;
+        if has_extension {
+            pos += 1;
+        }
+
+        if obu_type == OBU_SEQUENCE_HEADER {
+            return true;
+        }
+
+        if !has_size {
+            break;
+        }
+        let Some((obu_size, leb_len)) = read_leb128(&data[pos..]) else { break };
+        i = pos + leb_len + obu_size as usize;
+    }
+    false
+}
+
+/// Reads a little-endian base-128 varint (AV1 spec section 4.10.5), returning
+/// `(value, bytes_consumed)`.
+fn read_leb128(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in data.iter().enumerate().take(8) {
+        value |= ((byte & 0x7F) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn h265_keyframe_detected_by_irap_nal_type() {
+        // nal_unit_type 19 (IDR_W_RADL) packed into bits 1..=6 of the header byte.
+        let idr = [0, 0, 0, 1, 19 << 1, 0x01];
+        assert!(is_h265_keyframe(&idr));
+
+        // nal_unit_type 1 (TRAIL_R) is not an IRAP type.
+        let delta = [0, 0, 0, 1, 1 << 1, 0x01];
+        assert!(!is_h265_keyframe(&delta));
+    }
+
+    #[test]
+    fn vp8_keyframe_flag_is_the_inverted_low_bit() {
+        assert!(is_vp8_keyframe(&[0b1110_1110])); // low bit 0 => keyframe
+        assert!(!is_vp8_keyframe(&[0b1110_1111])); // low bit 1 => interframe
+        assert!(!is_vp8_keyframe(&[]));
+    }
+
+    #[test]
+    fn vp9_keyframe_requires_frame_marker_and_frame_type_zero() {
+        // frame_marker=0b10, profile bits=0b00 (profile 0), show_existing_frame=0,
+        // frame_type=0 (KEY_FRAME).
+        assert!(is_vp9_keyframe(&[0b1000_0000]));
+        // Same, but frame_type=1 (NON_KEY_FRAME).
+        assert!(!is_vp9_keyframe(&[0b1000_0100]));
+        // Invalid frame_marker.
+        assert!(!is_vp9_keyframe(&[0b0000_0000]));
+        assert!(!is_vp9_keyframe(&[]));
+    }
+
+    #[test]
+    fn av1_keyframe_detected_via_sequence_header_obu() {
+        // obu_type=1 (OBU_SEQUENCE_HEADER) in bits 3..=6, has_size_field=0.
+        let seq_header = [(OBU_SEQUENCE_HEADER << 3) as u8];
+        assert!(is_av1_keyframe(&seq_header));
+
+        // obu_type=3 (OBU_FRAME), no extension, no size field: not itself treated as a
+        // keyframe signal without a preceding sequence header OBU.
+        let frame_obu = [(3u8 << 3)];
+        assert!(!is_av1_keyframe(&frame_obu));
+    }
+
+    #[test]
+    fn read_leb128_decodes_multi_byte_varints() {
+        assert_eq!(read_leb128(&[0x05]), Some((5, 1)));
+        // 300 = 0b1_0010_1100 -> leb128 bytes: 0xAC 0x02
+        assert_eq!(read_leb128(&[0xAC, 0x02]), Some((300, 2)));
+        assert_eq!(read_leb128(&[]), None);
+    }
+}
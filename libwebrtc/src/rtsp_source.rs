@@ -0,0 +1,335 @@
+// Copyright 2025 LiveKit, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pure-Rust RTSP ingestion for the passthrough encoder.
+//!
+//! [`RtspVideoSource`] connects to an RTSP server, depacketizes H.264 (RFC 6184) or
+//! H.265 (RFC 7798) RTP payloads into access units, and yields them as
+//! [`EncodedVideoFrame`]s ready for [`EncodedVideoSource::push_frame`]. The stack is
+//! built entirely on [`retina`], so there is no ffmpeg (or any other native) dependency.
+//!
+//! Depacketization happens directly inside [`RtspVideoSource::poll_next`] rather than on
+//! a background thread handed off through a channel: RTSP/RTP demuxing is cheap compared
+//! to the context-switch and wakeup cost of an extra hop, and polling the `retina` session
+//! in place keeps backpressure working the same way it does for
+//! [`NativeVideoCapturerStream`][crate::video_capturer::NativeVideoCapturerStream].
+//!
+//! [`EncodedVideoSource::push_frame`]: crate::video_source::encoded::EncodedVideoSource::push_frame
+
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use livekit_runtime::Stream;
+use retina::{
+    client::{Credentials, Demuxed, PlayOptions, Session, SessionGroup, SessionOptions, Transport},
+    codec::{CodecItem, VideoParameters},
+};
+
+use crate::clock_source::ClockSource;
+use crate::native::file_video_source::{parse_avcc, AvcC};
+use crate::video_source::encoded::{EncodedVideoFrame, VideoCodecType};
+
+/// RTSP transport used to pull the RTP stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtspTransport {
+    /// RTP interleaved over the RTSP TCP connection (firewall-friendly, default).
+    Tcp,
+    /// RTP/RTCP over dedicated UDP ports.
+    Udp,
+}
+
+/// Connection options for [`RtspVideoSource::connect`].
+#[derive(Debug, Clone)]
+pub struct RtspSourceOptions {
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub transport: RtspTransport,
+}
+
+impl RtspSourceOptions {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), username: None, password: None, transport: RtspTransport::Tcp }
+    }
+
+    pub fn with_credentials(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self.password = Some(password.into());
+        self
+    }
+
+    pub fn with_transport(mut self, transport: RtspTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+}
+
+/// Error returned by [`RtspVideoSource::connect`]. Errors met while polling the stream are
+/// skipped and not surfaced.
+#[derive(Debug)]
+pub enum RtspSourceError {
+    Connect(String),
+    Describe(String),
+    UnsupportedCodec,
+    Rtp(String),
+}
+
+impl std::fmt::Display for RtspSourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RtspSourceError::Connect(e) => write!(f, "rtsp connect failed: {e}"),
+            RtspSourceError::Describe(e) => write!(f, "rtsp describe failed: {e}"),
+            RtspSourceError::UnsupportedCodec => write!(f, "no supported video codec in SDP"),
+            RtspSourceError::Rtp(e) => write!(f, "rtp depacketization error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RtspSourceError {}
+
+/// A video-capable RTSP session exposed as a [`Stream`] of [`EncodedVideoFrame`]s.
+///
+/// Each item is already a complete access unit (for H.264, the SDP's SPS/PPS prepended to
+/// keyframes that don't open with an SPS) with
+/// `rtp_timestamp` carried straight from the RTP header, so it can be handed directly to
+/// [`EncodedVideoSource::push_frame`][crate::video_source::encoded::EncodedVideoSource::push_frame].
+pub struct RtspVideoSource {
+    demuxed: Demuxed,
+    // Kept alive for the lifetime of the session; retina tears the session down on drop.
+    _session_group: Arc<SessionGroup>,
+    codec: VideoCodecType,
+    width: u32,
+    height: u32,
+    // SDP-advertised `sprop-parameter-sets`, re-prepended ahead of any keyframe retina
+    // hands back without them (some cameras only send SPS/PPS once, out of band).
+    parameter_sets: Option<AvcC>,
+    clock_source: ClockSource,
+}
+
+impl RtspVideoSource {
+    /// Connect to `options.url`, select the first video track, and start playing.
+    pub async fn connect(options: RtspSourceOptions) -> Result<Self, RtspSourceError> {
+        let url = options
+            .url
+            .parse()
+            .map_err(|e| RtspSourceError::Connect(format!("invalid url: {e}")))?;
+
+        let creds = match (&options.username, &options.password) {
+            (Some(username), Some(password)) => {
+                Some(Credentials { username: username.clone(), password: password.clone() })
+            }
+            _ => None,
+        };
+
+        let session_group = Arc::new(SessionGroup::default());
+        let mut session = Session::describe(
+            url,
+            SessionOptions::default()
+                .creds(creds)
+                .transport(match options.transport {
+                    RtspTransport::Tcp => Transport::Tcp(Default::default()),
+                    RtspTransport::Udp => Transport::Udp(Default::default()),
+                })
+                .session_group(session_group.clone()),
+        )
+        .await
+        .map_err(|e| RtspSourceError::Describe(e.to_string()))?;
+
+        let (video_idx, codec, params) = session
+            .streams()
+            .iter()
+            .enumerate()
+            .find_map(|(i, s)| match s.parameters() {
+                Some(retina::codec::ParametersRef::Video(v)) => {
+                    let codec = match v.rfc6381_codec() {
+                        c if c.starts_with("avc1") => VideoCodecType::H264,
+                        c if c.starts_with("hev1") || c.starts_with("hvc1") => {
+                            VideoCodecType::H265
+                        }
+                        _ => return None,
+                    };
+                    Some((i, codec, v.clone()))
+                }
+                _ => None,
+            })
+            .ok_or(RtspSourceError::UnsupportedCodec)?;
+
+        session
+            .setup(video_idx, retina::client::SetupOptions::default())
+            .await
+            .map_err(|e| RtspSourceError::Connect(e.to_string()))?;
+
+        let demuxed = session
+            .play(PlayOptions::default())
+            .await
+            .map_err(|e| RtspSourceError::Connect(e.to_string()))?
+            .demuxed()
+            .map_err(|e| RtspSourceError::Rtp(e.to_string()))?;
+
+        let (width, height) = video_dimensions(&params);
+        // `extra_data()` is the AVCDecoderConfigurationRecord retina builds from the SDP's
+        // `sprop-parameter-sets`; reuse the MP4 demuxer's avcC parser rather than writing a
+        // second one. H.265's SDP parameters produce an HVCC record instead, which this
+        // parser doesn't understand, so only attempt this for H.264.
+        let parameter_sets =
+            if codec == VideoCodecType::H264 { parse_avcc(params.extra_data()) } else { None };
+
+        Ok(Self {
+            demuxed,
+            _session_group: session_group,
+            codec,
+            width,
+            height,
+            parameter_sets,
+            clock_source: ClockSource::default(),
+        })
+    }
+
+    /// Use `source` to translate frame arrival into `ntp_time_ms` instead of the system
+    /// clock, e.g. [`ClockSource::ptp`] when the camera and SFU share a PTP grandmaster.
+    pub fn set_clock_source(&mut self, source: ClockSource) {
+        self.clock_source = source;
+    }
+
+    /// The video codec negotiated from the SDP.
+    pub fn codec(&self) -> VideoCodecType {
+        self.codec
+    }
+
+    /// Coded width, in pixels, from the SDP-advertised parameters.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Coded height, in pixels, from the SDP-advertised parameters.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Connect and hand back a source driven by its own dedicated Tokio runtime, for
+    /// callers (like a synchronous camera-ingestion thread) that have no ambient runtime of
+    /// their own to poll this `Stream` from.
+    pub fn connect_blocking(options: RtspSourceOptions) -> Result<BlockingRtspVideoSource, RtspSourceError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| RtspSourceError::Connect(e.to_string()))?;
+        let source = runtime.block_on(Self::connect(options))?;
+        Ok(BlockingRtspVideoSource { runtime, source })
+    }
+}
+
+/// An [`RtspVideoSource`] paired with the dedicated runtime it was connected on, so a
+/// synchronous caller can pull frames via [`BlockingRtspVideoSource::recv`] without
+/// running its own executor.
+pub struct BlockingRtspVideoSource {
+    runtime: tokio::runtime::Runtime,
+    source: RtspVideoSource,
+}
+
+impl BlockingRtspVideoSource {
+    /// Block the calling thread until the next frame (or end of stream) is available.
+    pub fn recv(&mut self) -> Option<EncodedVideoFrame> {
+        let source = &mut self.source;
+        self.runtime.block_on(std::future::poll_fn(|cx| Pin::new(&mut *source).poll_next(cx)))
+    }
+}
+
+/// Best-effort coded dimensions pulled from the SDP-level parameters; refined per-frame
+/// downstream, once [`crate::native::h264::ParameterSetTracker`] (run by whatever
+/// `EncodedVideoSource::push_frame` call consumes this stream) parses the in-band SPS.
+fn video_dimensions(params: &VideoParameters) -> (u32, u32) {
+    let (w, h) = params.pixel_dimensions();
+    (w as u32, h as u32)
+}
+
+/// True if `data` (Annex-B) already opens with a SPS (NAL type 7), i.e. prepending the
+/// cached parameter sets would just duplicate what's already there.
+fn starts_with_parameter_set(data: &[u8]) -> bool {
+    let after_start_code = match data {
+        [0, 0, 0, 1, rest @ ..] => rest,
+        [0, 0, 1, rest @ ..] => rest,
+        _ => return false,
+    };
+    after_start_code.first().map(|b| b & 0x1F == 7).unwrap_or(false)
+}
+
+impl Stream for RtspVideoSource {
+    type Item = EncodedVideoFrame;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.demuxed).poll_next(cx) {
+                Poll::Ready(Some(Ok(CodecItem::VideoFrame(frame)))) => {
+                    let is_keyframe = frame.is_random_access_point();
+                    let rtp_timestamp = frame.timestamp().timestamp() as u32;
+                    let capture_time_ms = frame.timestamp().elapsed().as_millis() as i64;
+                    let mut data = frame.into_data();
+
+                    // Some cameras only ever send SPS/PPS once in the SDP and never again
+                    // in-band; re-prepend from the cached sprop-parameter-sets so every
+                    // keyframe is independently decodable.
+                    if is_keyframe {
+                        if let Some(ref params) = this.parameter_sets {
+                            if !starts_with_parameter_set(&data) {
+                                let mut prefixed = Vec::with_capacity(data.len() + params.sps.len() + params.pps.len() + 8);
+                                prefixed.extend_from_slice(&[0, 0, 0, 1]);
+                                prefixed.extend_from_slice(&params.sps);
+                                prefixed.extend_from_slice(&[0, 0, 0, 1]);
+                                prefixed.extend_from_slice(&params.pps);
+                                prefixed.extend_from_slice(&data);
+                                data = prefixed;
+                            }
+                        }
+                    }
+
+                    let mut out = if is_keyframe {
+                        EncodedVideoFrame::keyframe(
+                            data,
+                            rtp_timestamp,
+                            capture_time_ms,
+                            this.width,
+                            this.height,
+                            this.codec,
+                        )
+                    } else {
+                        EncodedVideoFrame::delta_frame(
+                            data,
+                            rtp_timestamp,
+                            capture_time_ms,
+                            this.width,
+                            this.height,
+                            this.codec,
+                        )
+                    };
+                    // retina doesn't surface the RTCP sender report's NTP half directly on
+                    // depacketized frames, so approximate wall-clock capture time through
+                    // the configured `ClockSource` rather than the RTP-relative timestamp.
+                    out.ntp_time_ms = this.clock_source.now_ms();
+                    return Poll::Ready(Some(out));
+                }
+                // Non-video items (e.g. an audio track we didn't SETUP) never occur here
+                // since we only SETUP the video stream; keep polling defensively anyway.
+                Poll::Ready(Some(Ok(_))) => continue,
+                Poll::Ready(Some(Err(_))) => continue,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
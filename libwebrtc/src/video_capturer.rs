@@ -1,6 +1,10 @@
 use livekit_runtime::Stream;
 
-use crate::{imp::video_capturer as vc_imp, prelude::BoxVideoFrame};
+use crate::{
+    frame_cadence_adapter::{CadenceConfig, FrameCadenceAdapter},
+    imp::video_capturer as vc_imp,
+    prelude::BoxVideoFrame,
+};
 use std::{
     pin::Pin,
     sync::Arc,
@@ -51,6 +55,17 @@ impl VideoCapturer {
         Some((m, NativeVideoCapturerStream(stream)))
     }
 
+    /// Same as [`Self::open_device`], but with an explicit bound on how many queued frames
+    /// the stream holds before dropping the oldest one to make room for the newest.
+    pub fn open_device_with_queue_capacity(
+        unique_id: &str,
+        capacity: usize,
+    ) -> Option<(Self, NativeVideoCapturerStream)> {
+        let m = vc_imp::VideoCapturer::new(unique_id).map(|i| Self { sys_handle: i })?;
+        let stream = m.sys_handle.register_callback_with_capacity(capacity);
+        Some((m, NativeVideoCapturerStream(stream)))
+    }
+
     pub fn start(&self, capability: VideoCaptureCapability) -> bool {
         self.sys_handle.start(capability.into()) == 0
     }
@@ -99,3 +114,22 @@ impl Stream for NativeVideoCapturerStream {
         Pin::new(&mut self.get_mut().0).poll_next(cx)
     }
 }
+
+impl NativeVideoCapturerStream {
+    /// Wrap this stream with an opt-in frame cadence adapter (fps cap and, optionally,
+    /// zero-hertz idle repetition). See [`FrameCadenceAdapter`] for details.
+    pub fn with_cadence(self, config: CadenceConfig) -> FrameCadenceAdapter<Self> {
+        FrameCadenceAdapter::new(self, config)
+    }
+
+    /// Number of frames dropped so far because the internal queue was full when a new
+    /// frame arrived from the capture thread.
+    pub fn discarded_frame_count(&self) -> u64 {
+        self.0.discarded_frame_count()
+    }
+
+    /// Recycled I420 scratch-buffer pool shared by this stream's consumers.
+    pub fn frame_pool(&self) -> &std::sync::Arc<crate::imp::frame_pool::FramePool> {
+        self.0.frame_pool()
+    }
+}
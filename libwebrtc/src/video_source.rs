@@ -90,23 +90,61 @@ pub mod native {
 pub mod encoded {
     use std::fmt::{Debug, Formatter};
     use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
     use tokio::sync::mpsc;
 
     use super::VideoResolution;
+    use crate::clock_source::ClockSource;
+    use crate::frame_cadence_adapter::CadenceConfig;
+    use crate::native::h264::{ParameterSetTracker, TrackedFrame};
     use crate::native::passthrough_video_source::{
-        EncodedVideoFrame as PassthroughEncodedFrame, PassthroughEncoderFactory,
-        PassthroughEncoderHandle,
+        EncodedVideoFrame as PassthroughEncodedFrame, PassthroughCodec, PassthroughEncoderFactory,
+        PassthroughEncoderHandle, RateListener,
     };
 
+    /// Runtime state for the opt-in frame cadence layer on [`EncodedVideoSource`]: an fps
+    /// cap plus, for zero-hertz mode, the last keyframe so a repeated keyframe request can
+    /// be served from cache instead of demanding a fresh one from the upstream encoder.
+    struct CadenceState {
+        min_frame_interval: Duration,
+        zero_hertz: bool,
+        last_emit: Option<Instant>,
+        last_keyframe: Option<EncodedVideoFrame>,
+    }
+
+    impl CadenceState {
+        fn new(config: CadenceConfig) -> Self {
+            Self {
+                min_frame_interval: Duration::from_secs_f64(1.0 / config.max_fps.max(1) as f64),
+                zero_hertz: config.zero_hertz_min_fps.is_some(),
+                last_emit: None,
+                last_keyframe: None,
+            }
+        }
+    }
+
     /// Video codec type
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum VideoCodecType {
         H264,
+        H265,
         VP8,
         VP9,
         AV1,
     }
 
+    impl From<VideoCodecType> for PassthroughCodec {
+        fn from(codec: VideoCodecType) -> Self {
+            match codec {
+                VideoCodecType::H264 => PassthroughCodec::H264,
+                VideoCodecType::H265 => PassthroughCodec::H265,
+                VideoCodecType::VP8 => PassthroughCodec::VP8,
+                VideoCodecType::VP9 => PassthroughCodec::VP9,
+                VideoCodecType::AV1 => PassthroughCodec::AV1,
+            }
+        }
+    }
+
     /// Codec parameters
     #[derive(Debug, Clone)]
     pub struct CodecParameters {
@@ -188,6 +226,10 @@ pub mod encoded {
         encoder_factory: Arc<PassthroughEncoderFactory>,
         encoder_handle: Arc<Mutex<Option<PassthroughEncoderHandle>>>,
         frame_tx: Arc<Mutex<Option<mpsc::Sender<EncodedVideoFrame>>>>,
+        clock_source: Arc<Mutex<ClockSource>>,
+        cadence: Arc<Mutex<Option<CadenceState>>>,
+        h264_parameter_sets: Arc<Mutex<ParameterSetTracker>>,
+        idle_repeat_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     }
 
     impl Debug for EncodedVideoSource {
@@ -201,23 +243,139 @@ pub mod encoded {
     }
 
     impl EncodedVideoSource {
-        /// Create a new encoded video source
+        /// Create a new encoded video source.
+        ///
+        /// `codec` must also be set as the `video_codec` in the track's
+        /// `TrackPublishOptions` so SDP negotiation offers a matching payload type and
+        /// picks this passthrough factory instead of falling back to a software encoder.
         pub fn new(codec: VideoCodecType, width: u32, height: u32) -> Option<Self> {
-            // Only H.264 is currently supported for passthrough
-            if codec != VideoCodecType::H264 {
+            // H.265 has the plumbing (codec tag, keyframe detection) but isn't wired up
+            // end-to-end yet; H.264/VP8/VP9/AV1 are.
+            if codec == VideoCodecType::H265 {
                 return None;
             }
 
+            let encoder_factory = Arc::new(PassthroughEncoderFactory::new());
+            encoder_factory.set_codec(codec.into());
+
             Some(Self {
                 width,
                 height,
                 codec,
-                encoder_factory: Arc::new(PassthroughEncoderFactory::new()),
+                encoder_factory,
                 encoder_handle: Arc::new(Mutex::new(None)),
                 frame_tx: Arc::new(Mutex::new(None)),
+                clock_source: Arc::new(Mutex::new(ClockSource::default())),
+                cadence: Arc::new(Mutex::new(None)),
+                h264_parameter_sets: Arc::new(Mutex::new(ParameterSetTracker::new())),
+                idle_repeat_task: Arc::new(Mutex::new(None)),
             })
         }
 
+        /// Drive this source's absolute capture timestamps from `source` instead of the
+        /// default system clock, so a multi-sensor publisher (e.g. an RTSP camera and a
+        /// separately-clocked microphone track) can be aligned on a common reference.
+        pub fn set_clock_source(&self, source: ClockSource) {
+            *self.clock_source.lock().unwrap() = source;
+        }
+
+        /// Opt in to frame cadence shaping: an fps cap, and optionally zero-hertz mode
+        /// where a repeated keyframe request is served from the last cached keyframe
+        /// instead of being forwarded as a fresh [`request_keyframe`][Self::request_keyframe].
+        ///
+        /// In zero-hertz mode this also starts a background timer, mirroring
+        /// [`NativeVideoCapturerStream::with_cadence`][crate::video_capturer::NativeVideoCapturerStream::with_cadence]'s
+        /// idle-repeat behavior on the capture path: if no new frame arrives for
+        /// `zero_hertz_min_fps`'s interval, the last cached keyframe is re-injected on its
+        /// own so a newly-subscribed viewer on an otherwise-idle passthrough source still
+        /// gets content instead of waiting indefinitely for the upstream encoder to push
+        /// one. Calling this again (e.g. to change the fps cap) replaces any running timer.
+        pub fn enable_cadence(&self, config: CadenceConfig) {
+            *self.cadence.lock().unwrap() = Some(CadenceState::new(config));
+
+            if let Some(old) = self.idle_repeat_task.lock().unwrap().take() {
+                old.abort();
+            }
+            if let Some(min_fps) = config.zero_hertz_min_fps {
+                let idle_interval = Duration::from_secs_f64(1.0 / min_fps.max(1) as f64);
+                *self.idle_repeat_task.lock().unwrap() = Some(self.spawn_idle_repeat_task(idle_interval));
+            }
+        }
+
+        /// Spawns the zero-hertz idle-repeat timer. Holds only weak references to the
+        /// cadence/encoder state so the task can't keep `self` alive -- it notices `self`
+        /// was fully dropped (every clone gone) via a failed upgrade and exits instead of
+        /// looping forever on borrowed time.
+        fn spawn_idle_repeat_task(&self, idle_interval: Duration) -> tokio::task::JoinHandle<()> {
+            let cadence = Arc::downgrade(&self.cadence);
+            let encoder_handle = Arc::downgrade(&self.encoder_handle);
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(idle_interval).await;
+                    let (Some(cadence), Some(encoder_handle)) = (cadence.upgrade(), encoder_handle.upgrade())
+                    else {
+                        return;
+                    };
+
+                    let is_idle = {
+                        let state = cadence.lock().unwrap();
+                        match state.as_ref() {
+                            Some(s) if s.zero_hertz => s
+                                .last_emit
+                                .map(|last| last.elapsed() >= idle_interval)
+                                .unwrap_or(true),
+                            // Cadence was disabled, or re-enabled without zero-hertz mode:
+                            // `enable_cadence` already aborted this task in that case, but
+                            // bail out defensively rather than spin on a state that no
+                            // longer wants a timer.
+                            _ => return,
+                        }
+                    };
+                    if is_idle {
+                        Self::inject_cached_keyframe(&cadence, &encoder_handle);
+                    }
+                }
+            })
+        }
+
+        /// Re-injects `cadence`'s cached keyframe through `encoder_handle`, if one has been
+        /// cached and the encoder is initialized, stamping a fresh `last_emit` on success.
+        /// Shared between [`Self::service_keyframe_request_from_cache`] (triggered by an
+        /// explicit keyframe request) and the zero-hertz idle-repeat timer.
+        fn inject_cached_keyframe(
+            cadence: &Mutex<Option<CadenceState>>,
+            encoder_handle: &Mutex<Option<PassthroughEncoderHandle>>,
+        ) -> bool {
+            let cached = {
+                let cadence = cadence.lock().unwrap();
+                match cadence.as_ref() {
+                    Some(state) if state.zero_hertz => state.last_keyframe.clone(),
+                    _ => None,
+                }
+            };
+            let Some(cached) = cached else { return false };
+
+            let handle = encoder_handle.lock().unwrap();
+            let Some(ref encoder) = *handle else { return false };
+            let passthrough_frame = PassthroughEncodedFrame::new(
+                cached.data.clone(),
+                cached.rtp_timestamp,
+                cached.capture_time_ms,
+                true,
+                cached.width,
+                cached.height,
+                cached.codec.into(),
+            );
+            if encoder.inject_frame(&passthrough_frame).is_err() {
+                return false;
+            }
+            drop(handle);
+            if let Some(state) = cadence.lock().unwrap().as_mut() {
+                state.last_emit = Some(Instant::now());
+            }
+            true
+        }
+
         /// Get the width
         pub fn width(&self) -> u32 {
             self.width
@@ -269,19 +427,90 @@ pub mod encoded {
             false
         }
 
-        /// Push an encoded frame to the source
+        /// Push an encoded frame to the source.
+        ///
+        /// If [`enable_cadence`][Self::enable_cadence] was called, non-keyframes arriving
+        /// faster than the configured fps cap are silently dropped (returning `Ok(())`),
+        /// and keyframes are cached for zero-hertz keyframe-request handling.
         pub fn push_frame(&self, frame: &EncodedVideoFrame) -> Result<(), String> {
+            // H.264 frames are run through `native::h264::ParameterSetTracker`, which
+            // repairs keyframes missing their in-band SPS/PPS and corrects width/height
+            // from the SPS itself; for the other codecs, re-derive is_keyframe from the
+            // bitstream rather than trusting the caller, since a wrong PLI response here
+            // means a late-joining viewer never recovers.
+            let frame = if frame.codec == VideoCodecType::H264 {
+                let tracked = PassthroughEncodedFrame::new(
+                    frame.data.clone(),
+                    frame.rtp_timestamp,
+                    frame.capture_time_ms,
+                    frame.is_keyframe,
+                    frame.width,
+                    frame.height,
+                    PassthroughCodec::H264,
+                );
+                match self.h264_parameter_sets.lock().unwrap().process(tracked) {
+                    TrackedFrame::Emit(tracked) => EncodedVideoFrame {
+                        data: tracked.data,
+                        width: tracked.width,
+                        height: tracked.height,
+                        is_keyframe: tracked.is_keyframe,
+                        ..frame.clone()
+                    },
+                    TrackedFrame::DropNeedsKeyframe => {
+                        self.request_keyframe();
+                        return Ok(());
+                    }
+                }
+            } else {
+                let is_keyframe = match frame.codec {
+                    VideoCodecType::H264 => unreachable!(),
+                    VideoCodecType::VP8 => crate::native::video_codec::is_vp8_keyframe(&frame.data),
+                    VideoCodecType::VP9 => crate::native::video_codec::is_vp9_keyframe(&frame.data),
+                    VideoCodecType::AV1 => crate::native::video_codec::is_av1_keyframe(&frame.data),
+                    VideoCodecType::H265 => {
+                        crate::native::video_codec::is_h265_keyframe(&frame.data)
+                    }
+                };
+                EncodedVideoFrame { is_keyframe, ..frame.clone() }
+            };
+            let frame = &frame;
+
+            if !frame.is_keyframe {
+                let mut cadence = self.cadence.lock().unwrap();
+                if let Some(state) = cadence.as_mut() {
+                    let now = Instant::now();
+                    let allowed = state
+                        .last_emit
+                        .map(|last| now.duration_since(last) >= state.min_frame_interval)
+                        .unwrap_or(true);
+                    if !allowed {
+                        return Ok(());
+                    }
+                    state.last_emit = Some(now);
+                }
+            } else if let Some(state) = self.cadence.lock().unwrap().as_mut() {
+                if state.zero_hertz {
+                    state.last_keyframe = Some(frame.clone());
+                }
+                state.last_emit = Some(Instant::now());
+            }
+
             let encoder_handle = self.encoder_handle.lock().unwrap();
             if let Some(ref encoder) = *encoder_handle {
-                // Convert to passthrough encoded frame
-                let passthrough_frame = PassthroughEncodedFrame::new(
+                // Convert to passthrough encoded frame, stamping the real absolute
+                // capture time so the Absolute Capture Time header extension carries a
+                // cross-track-comparable value instead of the frame's local capture time.
+                let ntp_time_ms = self.clock_source.lock().unwrap().now_ms();
+                let mut passthrough_frame = PassthroughEncodedFrame::new(
                     frame.data.clone(),
                     frame.rtp_timestamp,
                     frame.capture_time_ms,
                     frame.is_keyframe,
                     frame.width,
                     frame.height,
+                    frame.codec.into(),
                 );
+                passthrough_frame.ntp_time_ms = ntp_time_ms;
 
                 encoder.inject_frame(&passthrough_frame).map_err(|e| format!("{}", e))
             } else {
@@ -289,6 +518,24 @@ pub mod encoded {
             }
         }
 
+        /// Service a pending keyframe request using the frame cadence layer.
+        ///
+        /// In zero-hertz mode, this re-injects the last cached keyframe (cheaper than
+        /// forcing the upstream source to produce a fresh one) and clears the request,
+        /// returning `true`. Returns `false` if cadence is disabled or no keyframe has
+        /// been cached yet, in which case callers should fall back to
+        /// [`request_keyframe`][Self::request_keyframe].
+        pub fn service_keyframe_request_from_cache(&self) -> bool {
+            if !Self::inject_cached_keyframe(&self.cadence, &self.encoder_handle) {
+                return false;
+            }
+            let encoder_handle = self.encoder_handle.lock().unwrap();
+            if let Some(ref encoder) = *encoder_handle {
+                encoder.clear_keyframe_request();
+            }
+            true
+        }
+
         /// Check if keyframe is requested
         pub fn is_keyframe_requested(&self) -> bool {
             let encoder_handle = self.encoder_handle.lock().unwrap();
@@ -314,6 +561,31 @@ pub mod encoded {
                 encoder.request_keyframe();
             }
         }
+
+        /// The target bitrate from WebRTC's most recent bitrate allocation, in bits per
+        /// second, or 0 if the encoder isn't initialized yet.
+        pub fn target_bitrate_bps(&self) -> u32 {
+            let encoder_handle = self.encoder_handle.lock().unwrap();
+            encoder_handle.as_ref().map(|e| e.target_bitrate_bps()).unwrap_or(0)
+        }
+
+        /// The framerate WebRTC's bitrate allocation expects this source to produce, or 0
+        /// if the encoder isn't initialized yet.
+        pub fn allocated_framerate(&self) -> u32 {
+            let encoder_handle = self.encoder_handle.lock().unwrap();
+            encoder_handle.as_ref().map(|e| e.allocated_framerate()).unwrap_or(0)
+        }
+
+        /// Register a listener invoked whenever WebRTC updates the bitrate allocation for
+        /// this source's encoder, so an upstream pipeline (e.g. an NVENC encoder thread)
+        /// can retarget its bitrate, framerate or resolution ladder rung in response to
+        /// congestion instead of overshooting the available uplink.
+        pub fn set_rate_listener(&self, listener: std::sync::Arc<dyn RateListener>) {
+            let encoder_handle = self.encoder_handle.lock().unwrap();
+            if let Some(ref encoder) = *encoder_handle {
+                encoder.set_rate_listener(listener);
+            }
+        }
     }
 }
 
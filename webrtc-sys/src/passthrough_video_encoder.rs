@@ -12,23 +12,69 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use crate::impl_thread_safety;
 
+/// Implemented by Rust types that want to react to WebRTC's bitrate allocation
+/// (`SetRates`) decisions for a passthrough encoder, e.g. to lower an upstream hardware
+/// encoder's target bitrate in response to congestion.
+pub trait RateListener: Send + Sync {
+    fn on_rate_update(&self, bitrate_bps: u32, framerate_fps: u32);
+}
+
+/// Bridges a boxed [`RateListener`] trait object across the cxx boundary; cxx's
+/// `extern "Rust"` blocks need a concrete type, not a trait, so this plays the same role
+/// `video_track::VideoSinkWrapper` plays for frame callbacks.
+pub struct RateListenerWrapper(Arc<dyn RateListener>);
+
+impl RateListenerWrapper {
+    pub fn new(listener: Arc<dyn RateListener>) -> Self {
+        Self(listener)
+    }
+
+    fn on_rate_update(&self, bitrate_bps: u32, framerate_fps: u32) {
+        self.0.on_rate_update(bitrate_bps, framerate_fps);
+    }
+}
+
 #[cxx::bridge(namespace = "livekit")]
 pub mod ffi {
+    use super::RateListenerWrapper;
+
+    /// Codec carried by a passthrough-injected bitstream. Mirrors
+    /// `libwebrtc::video_source::encoded::VideoCodecType`, kept as a separate shared enum
+    /// since the cxx bridge can't reuse a pure-Rust type directly.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum PassthroughCodec {
+        H264,
+        H265,
+        VP8,
+        VP9,
+        AV1,
+    }
+
+    extern "Rust" {
+        type RateListenerWrapper;
+
+        fn on_rate_update(self: &RateListenerWrapper, bitrate_bps: u32, framerate_fps: u32);
+    }
+
     unsafe extern "C++" {
         include!("livekit/passthrough_video_encoder.h");
 
         type PassthroughVideoEncoder;
         type PassthroughVideoEncoderFactory;
+        type PassthroughCodec;
 
         /// Create a new passthrough video encoder factory
         fn new_passthrough_video_encoder_factory() -> SharedPtr<PassthroughVideoEncoderFactory>;
 
-        /// Inject an encoded H.264 frame into the encoder
+        /// Inject an encoded frame of the given codec into the encoder.
         /// Returns 0 on success, non-zero error code on failure
         unsafe fn passthrough_encoder_inject_frame(
             encoder: *mut PassthroughVideoEncoder,
+            codec: PassthroughCodec,
             data: &[u8],
             rtp_timestamp: u32,
             capture_time_ms: i64,
@@ -38,6 +84,13 @@ pub mod ffi {
             height: u32,
         ) -> i32;
 
+        /// Select which codec this factory's next `Create()` call should hand out a
+        /// passthrough encoder for, matching the codec negotiated over SDP.
+        fn passthrough_factory_set_codec(
+            factory: *const PassthroughVideoEncoderFactory,
+            codec: PassthroughCodec,
+        );
+
         /// Check if a keyframe has been requested by the receiver
         unsafe fn passthrough_encoder_is_keyframe_requested(
             encoder: *const PassthroughVideoEncoder,
@@ -49,6 +102,24 @@ pub mod ffi {
         /// Request a keyframe (can be called when we know we need one)
         unsafe fn passthrough_encoder_request_keyframe(encoder: *mut PassthroughVideoEncoder);
 
+        /// The target bitrate from WebRTC's most recent `SetRates` bitrate allocation.
+        unsafe fn passthrough_encoder_target_bitrate_bps(
+            encoder: *const PassthroughVideoEncoder,
+        ) -> u32;
+
+        /// The framerate WebRTC's bitrate allocation expects the source to produce.
+        unsafe fn passthrough_encoder_allocated_framerate(
+            encoder: *const PassthroughVideoEncoder,
+        ) -> u32;
+
+        /// Register a listener that's invoked every time WebRTC calls `SetRates` with a
+        /// new allocation, so an upstream encoder can react to congestion control instead
+        /// of polling `target_bitrate_bps`/`allocated_framerate`.
+        unsafe fn passthrough_encoder_set_rate_listener(
+            encoder: *mut PassthroughVideoEncoder,
+            listener: Box<RateListenerWrapper>,
+        );
+
         /// Get the last created encoder from the factory
         /// Note: The returned pointer is only valid until the next Create() call
         /// or until the encoder is destroyed